@@ -0,0 +1,43 @@
+//! Decrypting a field of a large struct via `EnvelopeBorrow`, compared with decrypting the whole
+//! struct. Run with `cargo bench`.
+#![feature(ptr_metadata)]
+#![feature(test)]
+#![allow(non_camel_case_types)]
+
+extern crate test;
+
+use std::hint::black_box;
+
+use place_projections::*;
+use test::Bencher;
+
+#[derive(Clone, Copy, NoUninit)]
+struct Big {
+    header: u32,
+    #[expect(unused)]
+    payload: [u8; 64 * 1024],
+}
+mk_field_proj!(struct header(Big.header: u32));
+
+const KEY: [u8; 32] = [42; 32];
+
+fn envelope() -> EncryptedEnvelope<Big> {
+    let big = Big {
+        header: 42,
+        payload: [0; 64 * 1024],
+    };
+    EncryptedEnvelope::encrypt(big, KEY)
+}
+
+#[bench]
+fn decrypt_whole_then_read_field(b: &mut Bencher) {
+    let env = envelope();
+    b.iter(|| assert_eq!(black_box(&env).decrypt(KEY).header, 42));
+}
+
+#[bench]
+fn decrypt_field_only(b: &mut Bencher) {
+    let env = envelope();
+    let env_borrow = black_box(env.borrow(KEY));
+    b.iter(|| assert_eq!(black_box(p!(safe(*env_borrow).header)), 42));
+}
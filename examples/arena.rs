@@ -0,0 +1,87 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, Copy, PartialEq, Debug)]
+struct Pos {
+    x: i32,
+    y: i32,
+}
+
+#[derive(FieldProjections)]
+struct Team {
+    score: u32,
+    leader: Idx<Unit>,
+}
+
+#[derive(FieldProjections)]
+struct Unit {
+    pos: Pos,
+    hp: u32,
+    team: Idx<Team>,
+}
+
+fn main() {
+    let mut teams = Arena::new();
+    let mut units = Arena::new();
+    let red = teams.insert(Team {
+        score: 0,
+        leader: Idx::dangling(),
+    });
+    let spawn = |units: &mut Arena<Unit>, px, py| {
+        units.insert(Unit {
+            pos: Pos { x: px, y: py },
+            hp: 10,
+            team: red,
+        })
+    };
+    let a = spawn(&mut units, 0, 0);
+    let b = spawn(&mut units, 5, 5);
+    teams.get_mut(red).unwrap().leader = a;
+
+    // Safe field access through an `ArenaRef`, from a shared reference to the arena.
+    let unit_a = units.at(a);
+    p!(safe(*unit_a).pos.x = 3);
+    let hp_a = p!(safe(*unit_a).hp);
+    p!(safe(*unit_a).hp = hp_a - 4);
+    let pos_of_a: ArenaRef<Pos, Unit> = p!(safe @_ (*unit_a).pos);
+    assert_eq!(p!(safe * pos_of_a), Pos { x: 3, y: 0 });
+    assert_eq!(units.get_mut(a).unwrap().hp, 6);
+
+    // Follow `Idx` fields into the other arena, and back.
+    teams.with_entered(|| {
+        units.with_entered(|| {
+            let unit_b = units.at(b);
+            unsafe {
+                p!((*(*unit_b).team).score = 7);
+                assert_eq!(p!((*(*(*unit_b).team).leader).pos.x), 3);
+                p!((*(*(*unit_b).team).leader).hp = 1);
+                let leader_pos: ArenaRef<Pos, Unit> = p!(@_ (*(*(*unit_b).team).leader).pos);
+                assert_eq!(p!(safe * leader_pos), Pos { x: 3, y: 0 });
+            }
+        })
+    });
+    assert_eq!(teams.get_mut(red).unwrap().score, 7);
+    assert_eq!(units.get_mut(a).unwrap().hp, 1);
+
+    // Removing a value makes its handles stale, even once the slot is reused.
+    assert_eq!(units.remove(a).map(|u| u.hp), Some(1));
+    assert!(units.remove(a).is_none());
+    let c = spawn(&mut units, 9, 9);
+    assert_eq!(c.index(), a.index());
+    assert!(!units.contains(a) && units.contains(c));
+    assert!(units.try_at(a).is_none());
+    assert_eq!(units.len(), 2);
+
+    // Following the stale leader handle panics.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let followed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        teams.with_entered(|| {
+            units.with_entered(|| {
+                let unit_c = units.at(c);
+                unsafe { p!((*(*(*unit_c).team).leader).hp) }
+            })
+        })
+    }));
+    std::panic::set_hook(hook);
+    assert!(followed.is_err());
+}
@@ -1,7 +1,9 @@
 #![feature(ptr_metadata)]
-#![feature(trace_macros)]
+#![allow(clippy::disallowed_names)]
 #![allow(non_camel_case_types)]
 
+use std::ptr::NonNull;
+
 use place_projections::*;
 
 type Ptr<T> = *mut T;
@@ -52,9 +54,21 @@ fn main() {
         assert_eq!(p!((*p).a.b.n), 42); // read from place
         let ptr_n: Ptr<u32> = p!(@Ptr (*p).a.b.n); // borrow place
         assert_eq!(*ptr_n, 42);
+        let ptr_b = p!(@*const _ (*p).a.b); // borrow as a pointer type
+        assert_eq!(ptr_b, &raw const foo.a.b);
+        let nonnull = NonNull::new(p).unwrap();
+        let nonnull_b = p!(@std::ptr::NonNull (*nonnull).a.b); // borrow as a path
+        assert_eq!(nonnull_b.as_ptr(), &raw mut foo.a.b);
         p!((*p).a.b.n = 73); // write to place
         assert_eq!(*ptr_n, 73);
         assert_eq!(p!((*(*p).ptr_a).b.n), 73); // read via other ptr
         assert_eq!(p!(p.*.ptr_a.*.b.n), 73); // postfix deref is fun
+
+        // These don't compile:
+        // p!((*p).a.foo.n); // field `foo` has no projection in scope (it names the local)
+        // p!((*p).a.c.n); // cannot find value `c` in this scope (a misspelled field)
+        // p!((*p).a.b.n.*); // the trait bound `u32: HasPlace` is not satisfied
+        // p!(@Ptr (*p).a = 1); // cannot assign to a borrow
+        // p!(p.a); // place expressions must start with a deref
     }
 }
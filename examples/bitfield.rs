@@ -0,0 +1,71 @@
+use place_projections::*;
+
+/// TCP-style flags and window packed into one `u32`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, FieldProjections)]
+#[repr(C)]
+struct Flags {
+    raw: u32,
+    #[bits(0..1)]
+    ack: Bits<bool>,
+    #[bits(1..2)]
+    fin: Bits<bool>,
+    #[bits(3..7)]
+    offset: Bits<u8>,
+    #[bits(16..32)]
+    window: Bits<u16>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, FieldProjections)]
+#[repr(C)]
+struct Header {
+    seq: u32,
+    flags: Flags,
+    // Bit fields can also sit directly in the struct.
+    class_ver: u8,
+    #[bits(0..4)]
+    version: Bits<u8>,
+    #[bits(4..8)]
+    class: Bits<u8>,
+}
+
+fn main() {
+    let mut header = Header::default();
+    let hdr = BitMut::new(&mut header);
+    unsafe {
+        p!((*hdr).flags.ack = true);
+        p!((*hdr).flags.offset = 0b1011);
+        p!((*hdr).flags.window = 0xabcd);
+        p!((*hdr).version = 4);
+        p!((*hdr).class = 0xf);
+        assert!(p!((*hdr).flags.ack));
+        assert!(!p!((*hdr).flags.fin));
+        assert_eq!(p!((*hdr).flags.offset), 0b1011);
+        assert_eq!(p!((*hdr).flags.window), 0xabcd);
+
+        // Writes only touch their bits.
+        p!((*hdr).flags.ack = false);
+        p!((*hdr).flags.fin = true);
+        assert_eq!(p!((*hdr).flags.offset), 0b1011);
+
+        // Sub-structs can be borrowed as bit-field pointers too.
+        let flags_ref: BitRef<Flags> = p!(@_ (*hdr).flags);
+        assert_eq!(p!((*flags_ref).window), 0xabcd);
+    }
+    assert_eq!(header.flags.raw, 0xabcd_0000 | 0b1011 << 3 | 0b10);
+    assert_eq!(header.class_ver, 0xf4);
+
+    let hdr = BitMut::new(&mut header);
+    std::panic::set_hook(Box::new(|_| {}));
+    let res = std::panic::catch_unwind(|| unsafe { p!((*hdr).version = 16) });
+    assert!(res.is_err(), "16 doesn't fit in 4 bits");
+    let _ = std::panic::take_hook();
+
+    // Doesn't compile: `seq` isn't a bit field.
+    // unsafe { p!((*hdr).seq) };
+    // Doesn't compile: bit fields aren't places, so ordinary pointers can't read them, even
+    // unsafely.
+    // let r = &header;
+    // p!(safe (*r).flags.ack);
+    // let r = &raw const header;
+    // unsafe { p!((*r).flags.ack) };
+}
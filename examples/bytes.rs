@@ -0,0 +1,104 @@
+use place_projections::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Validate)]
+#[repr(u8)]
+enum MsgType {
+    Ping = 1,
+    Data = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FieldProjections, Validate)]
+#[repr(C)]
+struct Item {
+    code: char,
+    id: u16,
+    active: bool,
+}
+
+/// A message with a variable number of items; `count` says how many.
+#[derive(FieldProjections)]
+#[repr(C)]
+struct Message {
+    msg_type: MsgType,
+    urgent: bool,
+    count: u16,
+    #[len(count)]
+    items: [Item],
+}
+
+/// Aligned storage, so that items can also be borrowed as references.
+#[repr(C, align(8))]
+struct Buf([u8; 64]);
+
+fn encode(ty: u8, urg: u8, n: u16, entries: &[(u32, u16, u8)]) -> Buf {
+    let mut bytes = vec![ty, urg];
+    bytes.extend(n.to_ne_bytes());
+    for &(c, i, a) in entries {
+        bytes.extend(c.to_ne_bytes());
+        bytes.extend(i.to_ne_bytes());
+        bytes.extend([a, 0]);
+    }
+    let mut buf = Buf([0xff; 64]);
+    buf.0[..bytes.len()].copy_from_slice(&bytes);
+    buf
+}
+
+fn main() {
+    let buf = encode(2, 1, 2, &[('a' as u32, 7, 1), ('b' as u32, 9, 0)]);
+    let bytes = &buf.0[..20];
+    let msg: BytesRef<Message> = BytesRef::with_tail(bytes).unwrap();
+    assert_eq!(msg.metadata(), 2);
+    assert_eq!(p!(safe(*msg).msg_type), MsgType::Data);
+    assert!(p!(safe(*msg).urgent));
+    assert_eq!(p!(safe(*msg).items[1].id), 9);
+    assert_eq!(p!(safe(*msg).items[0].code), 'a');
+    // Reads copy out only the requested place; references point into the buffer.
+    let first: &Item = p!(safe @&_ (*msg).items[0]);
+    assert_eq!(
+        *first,
+        Item {
+            code: 'a',
+            id: 7,
+            active: true
+        }
+    );
+    assert!(std::ptr::eq(
+        std::ptr::from_ref(first).cast(),
+        bytes[4..].as_ptr()
+    ));
+    // Sub-places keep checking against the buffer.
+    let all: BytesRef<[Item]> = p!(safe @_ (*msg).items);
+    assert_eq!(all.offset(), 4);
+    assert_eq!(all.metadata(), 2);
+
+    // The length comes from the untrusted header: a buffer too short for it is rejected.
+    let buf = encode(2, 0, 50, &[]);
+    assert!(matches!(
+        BytesRef::<Message>::with_tail(&buf.0),
+        Err(BytesError::OutOfBounds { .. })
+    ));
+
+    // Bit patterns are checked before reading.
+    let buf = encode(3, 2, 1, &[(0xd800, 1, 5)]);
+    let msg: BytesRef<Message> = BytesRef::with_tail(&buf.0[..12]).unwrap();
+    assert_eq!(p!(safe(*msg).count), 1);
+    let type_ref: BytesRef<MsgType> = p!(safe @_ (*msg).msg_type);
+    assert!(matches!(
+        type_ref.try_read(),
+        Err(BytesError::Invalid { offset: 0, .. })
+    ));
+    let item: BytesRef<Item> = p!(safe @_ (*msg).items[0]);
+    assert_eq!(p!(safe(*item).id), 1);
+    assert!(
+        p!(safe @BytesRef (*item).code).try_read().is_err(),
+        "a surrogate isn't a char"
+    );
+    assert!(p!(safe @BytesRef (*item).active).try_read().is_err());
+    assert!(item.try_read().is_err());
+
+    // Place operations panic instead.
+    std::panic::set_hook(Box::new(|_| {}));
+    assert!(std::panic::catch_unwind(|| p!(safe(*msg).urgent)).is_err());
+    assert!(std::panic::catch_unwind(|| p!(safe(*msg).items[1].id)).is_err());
+    let _ = std::panic::take_hook();
+}
@@ -0,0 +1,65 @@
+#![feature(ptr_metadata)]
+#![allow(non_camel_case_types)]
+
+use place_projections::*;
+
+/// A memory-mapped register block.
+#[repr(C)]
+struct Regs {
+    status: u32,
+    ctrl: Ctrl,
+}
+mk_field_proj!(struct status(Regs.status: u32));
+mk_field_proj!(struct ctrl(Regs.ctrl: Ctrl));
+
+#[repr(C)]
+struct Ctrl {
+    enable: u32,
+    mode: u16,
+}
+mk_field_proj!(struct enable(Ctrl.enable: u32));
+mk_field_proj!(struct mode(Ctrl.mode: u16));
+
+// Offsets of nested fields, computed at compile time.
+const CTRL_MODE: SizedProj<Regs, u16> = ctrl.as_sized().then(mode.as_sized());
+const CTRL_ENABLE: SizedProj<Regs, u32> = ctrl.as_sized().then(enable.as_sized());
+const CTRL: SizedProj<Regs, Ctrl> = ctrl.as_sized();
+
+// A composed offset can be used as an array length.
+static PADDING_BEFORE_MODE: [u8; CTRL_MODE.byte_offset()] = [0; CTRL_MODE.byte_offset()];
+
+/// A table of register names and offsets, built at compile time.
+static REGISTER_MAP: [(&str, usize); 3] = [
+    ("status", status.as_sized().byte_offset()),
+    ("ctrl.enable", CTRL_ENABLE.byte_offset()),
+    ("ctrl.mode", CTRL_MODE.byte_offset()),
+];
+
+fn main() {
+    assert_eq!(PADDING_BEFORE_MODE.len(), 8);
+    assert_eq!(
+        REGISTER_MAP,
+        [("status", 0), ("ctrl.enable", 4), ("ctrl.mode", 8)]
+    );
+
+    // Const projections agree with the runtime ones and can be used as normal projections.
+    assert_eq!(CTRL_MODE.byte_offset(), ctrl.compose(mode).offset(()));
+    let mut regs = Regs {
+        status: 0,
+        ctrl: Ctrl { enable: 1, mode: 3 },
+    };
+    let p: *mut Regs = &raw mut regs;
+    unsafe {
+        assert_eq!(CTRL_MODE.read(&raw const p), 3);
+        CTRL_ENABLE.write((&raw const p).cast_mut(), 0);
+    }
+    assert_eq!(regs.ctrl.enable, 0);
+
+    // Including in `p!`, alone or followed by other projections.
+    unsafe {
+        assert_eq!(p!((*p).CTRL_MODE), 3);
+        p!((*p).CTRL_ENABLE = 1);
+        assert_eq!(p!((*p).CTRL.mode), 3);
+    }
+    assert_eq!(regs.ctrl.enable, 1);
+}
@@ -0,0 +1,60 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, PartialEq, Debug)]
+struct Net {
+    port: u16,
+    hosts: Vec<String>,
+}
+
+#[derive(FieldProjections, Clone, PartialEq, Debug)]
+struct Config {
+    name: String,
+    net: Net,
+    retries: u32,
+}
+
+fn main() {
+    let cfg = CowPtr::new(Config {
+        name: "prod".to_owned(),
+        net: Net {
+            port: 80,
+            hosts: vec!["a".to_owned(), "b".to_owned()],
+        },
+        retries: 3,
+    });
+
+    // Reading and borrowing doesn't clone.
+    let v1 = cfg.snapshot();
+    assert_eq!(p!(safe(*cfg).net.port), 80);
+    let host_list: &Vec<String> = unsafe { p!(@&_ (*cfg).net.hosts) };
+    assert_eq!(host_list.len(), 2);
+    assert!(cfg.ptr_eq(&v1) && cfg.is_shared());
+
+    // The first write clones the shared value, later ones edit the copy in place.
+    p!(safe(*cfg).net.port = 8080);
+    assert!(!cfg.ptr_eq(&v1) && !cfg.is_shared());
+    let edited = std::rc::Rc::as_ptr(&cfg.to_rc());
+    p!(safe(*cfg).retries = 5);
+    p!(safe(*cfg).name = "staging".to_owned());
+    p!(safe(*cfg).net.hosts = vec!["c".to_owned()]);
+    assert!(std::ptr::eq(edited, &*cfg.to_rc()));
+
+    // The snapshot still has the old values.
+    let old = v1.to_rc();
+    assert_eq!(old.net.port, 80);
+    assert_eq!(old.name, "prod");
+    assert_eq!(old.net.hosts, ["a", "b"]);
+    drop(old);
+    assert_eq!(
+        *cfg.to_rc(),
+        Config {
+            name: "staging".to_owned(),
+            net: Net {
+                port: 8080,
+                hosts: vec!["c".to_owned()],
+            },
+            retries: 5,
+        }
+    );
+    assert_eq!(std::rc::Rc::strong_count(&v1.into_rc()), 1);
+}
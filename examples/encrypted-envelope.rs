@@ -1,114 +1,99 @@
 #![feature(ptr_metadata)]
 
-use std::marker::PhantomData;
-
 use place_projections::*;
 
-#[derive(Debug, Clone, Copy)]
-pub struct Key(u8);
-
-/// An encrypted `T`. This only holds the encrypted bytes; a key is required to access the `T`
-/// value.
-pub struct EncryptedEnvelope<T> {
-    bytes: Box<[u8]>,
-    // Value used to check that the key is the right one. It is not at all the key of course haha.
-    key_check: u8,
-    phantom: PhantomData<T>,
-}
-
-pub struct EnvelopeBorrow<'a, Whole, Part> {
-    env: &'a EncryptedEnvelope<Whole>,
-    proj: Box<dyn Projection<Source = Whole, Target = Part>>,
-}
-
-impl<T> EncryptedEnvelope<T> {
-    pub fn encrypt(x: T, key: Key) -> Self {
-        unsafe {
-            // Alloc a slice of zeros.
-            let mut b: Box<[u8]> = Box::new_zeroed_slice(size_of::<T>()).assume_init();
-            // Write the bytes of T.
-            b.as_mut_ptr().cast::<T>().write_unaligned(x);
-            // Xor all the bits with the key.
-            for b in b.iter_mut() {
-                *b ^= key.0;
-            }
-            Self {
-                bytes: b,
-                key_check: !key.0,
-                phantom: PhantomData,
-            }
-        }
-    }
-    pub fn decrypt(&self, key: Key) -> T {
-        // Check the key is the right one (otherwise we risk creating an invalid `T`).
-        assert_eq!(!key.0, self.key_check);
-        unsafe {
-            let mut b: Box<[u8]> = self.bytes.clone();
-            // Xor all the bits with the key.
-            for b in b.iter_mut() {
-                *b ^= key.0;
-            }
-            b.as_ptr().cast::<T>().read_unaligned()
-        }
-    }
-
-    // `T: 'static` is required for `dyn` for some reason.
-    pub fn borrow(&self) -> EnvelopeBorrow<'_, T, T>
-    where
-        T: 'static,
-    {
-        EnvelopeBorrow {
-            env: self,
-            proj: Box::new(NoopProj::default()),
-        }
-    }
-}
-
-impl<'a, Whole, Part> EnvelopeBorrow<'a, Whole, Part> {
-    pub fn decrypt(&self, key: Key) -> Part {
-        let whole: &Whole = &self.env.decrypt(key);
-        unsafe { self.proj.read(&raw const whole) }
-    }
-}
-
-impl<T> HasPlace for EncryptedEnvelope<T> {
-    type Target = T;
-}
-impl<'a, Whole, Part> HasPlace for EnvelopeBorrow<'a, Whole, Part> {
-    type Target = Part;
-}
-
-unsafe impl<'a, 'b: 'a, Whole, P> PlaceBorrow<'a, P, EnvelopeBorrow<'a, Whole, P::Target>>
-    for EnvelopeBorrow<'b, Whole, P::Source>
-where
-    P: Projection + ?Sized,
-    Whole: 'static,
-    P::Source: Sized + 'static,
-    P::Target: Sized + 'static,
-{
-    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
-    unsafe fn borrow(ptr: *const Self, p: &P) -> EnvelopeBorrow<'a, Whole, P::Target> {
-        let this = unsafe { &*ptr };
-        EnvelopeBorrow {
-            env: this.env,
-            proj: Box::new(this.proj.as_sized().compose(p.as_sized())),
-        }
-    }
-}
-
 fn main() {
     #![allow(non_camel_case_types)]
+    // Check our ChaCha20 against the test vector from RFC 8439, section 2.4.2.
+    let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+    let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+    let mut bytes =
+        *b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for \
+                       the future, sunscreen would be it.";
+    ChaCha20::apply_keystream(&key, &nonce, 64, &mut bytes);
+    assert_eq!(bytes[..8], [0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80]);
+    assert_eq!(bytes[bytes.len() - 4..], [0x5e, 0x42, 0x87, 0x4d]);
+
+    // Encrypting reads all the bytes of the value, so it can't have padding.
+    #[derive(Clone, Copy, NoUninit)]
     struct Foo {
         data: u32,
+        other: u16,
+        flags: u16,
     }
+    // Doesn't compile: `NoUninit` types can't have padding.
+    // #[derive(Clone, Copy, NoUninit)]
+    // struct Padded {
+    //     data: u32,
+    //     other: u16,
+    // }
     mk_field_proj!(struct data(Foo.data: u32));
+    mk_field_proj!(struct other(Foo.other: u16));
 
-    let key = Key(42);
-    let env = EncryptedEnvelope::encrypt(Foo { data: 123456789u32 }, key);
+    let key = [42; 32];
+    let mut env: EncryptedEnvelope<_> = EncryptedEnvelope::encrypt(
+        Foo {
+            data: 123456789u32,
+            other: 7,
+            flags: 0,
+        },
+        key,
+    );
     assert_eq!(env.decrypt(key).data, 123456789);
 
-    let env_borrow = env.borrow();
+    // Write to a field through a mutable borrow.
+    let env_mut = env.borrow_mut(key);
+    p!(safe(*env_mut).data = 987654321);
+    let other_mut = unsafe { p!(@EnvelopeBorrowMut<'_, _, _> (*env_mut).other) };
+    assert_eq!(other_mut.decrypt(), 7);
+    p!(safe(*other_mut) = 8);
+    let decrypted = env.decrypt(key);
+    assert_eq!(decrypted.data, 987654321);
+    assert_eq!(decrypted.other, 8);
+    assert_eq!(decrypted.flags, 0);
+
+    let env_borrow = env.borrow(key);
+    // Read a field.
+    assert_eq!(p!(safe(*env_borrow).other), 8);
     // Project to a field.
-    let field_borrow: EnvelopeBorrow<'_, _, _> = unsafe { p!(@_ (*env_borrow).data) };
-    assert_eq!(field_borrow.decrypt(key), 123456789);
+    let field_borrow = p!(safe @EnvelopeBorrow<'_, _, _> (*env_borrow).data);
+    assert_eq!(field_borrow.decrypt(), 987654321);
+
+    // A wrong key is detected before anything gets decrypted.
+    std::panic::set_hook(Box::new(|_| {}));
+    assert!(std::panic::catch_unwind(|| env.decrypt([43; 32])).is_err());
+    let _ = std::panic::take_hook();
+
+    // Unsized envelopes store the length next to the ciphertext.
+    let mut env = EncryptedEnvelope::<[u32]>::encrypt_copy(&[10, 20, 30, 40], key);
+    assert_eq!(env.metadata(), 4);
+    assert_eq!(env.decrypt(key), [10, 20, 30, 40]);
+    let env_borrow = env.borrow(key);
+    // Decrypt a single element or a subslice.
+    assert_eq!(p!(safe(*env_borrow)[2]), 30);
+    let sub = p!(safe @EnvelopeBorrow<'_, _, _> (*env_borrow)[1..3]);
+    assert_eq!(sub.decrypt(), [20, 30]);
+    let env_mut = env.borrow_mut(key);
+    unsafe { p!((*env_mut)[3] = 41) };
+    assert_eq!(env.decrypt(key), [10, 20, 30, 41]);
+
+    let env = EncryptedEnvelope::<str>::encrypt_copy("héllo world", key);
+    assert_eq!(env.decrypt(key), "héllo world");
+    let env_borrow = env.borrow(key);
+    // Char boundaries can't be checked without decrypting, so this needs `unsafe`. Decrypting
+    // checks that the result is valid UTF-8.
+    let world = unsafe { p!(@EnvelopeBorrow<'_, _, _> (*env_borrow)[7..12]) };
+    assert_eq!(world.decrypt(), "world");
+
+    // The test cipher works too.
+    let env = EncryptedEnvelope::<_, XorCipher>::encrypt(
+        Foo {
+            data: 1,
+            other: 2,
+            flags: 3,
+        },
+        42,
+    );
+    let env_borrow = env.borrow(42);
+    assert_eq!(p!(safe(*env_borrow).other), 2);
 }
@@ -0,0 +1,67 @@
+use place_projections::*;
+
+/// An IPv4 header, without options. All multi-byte fields are big-endian on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, FieldProjections, EndianConvert)]
+#[repr(C)]
+struct Ipv4Header {
+    version_ihl: u8,
+    tos: u8,
+    total_len: u16,
+    id: u16,
+    frag: u16,
+    ttl: u8,
+    proto: u8,
+    checksum: u16,
+    src: [u8; 4],
+    dst: [u8; 4],
+}
+
+/// A little-endian file record, with a nested struct and a float.
+#[derive(Clone, Copy, Debug, PartialEq, FieldProjections, EndianConvert)]
+#[repr(C)]
+struct Sample {
+    tag: u32,
+    pos: Pos,
+}
+#[derive(Clone, Copy, Debug, PartialEq, FieldProjections, EndianConvert)]
+#[repr(C)]
+struct Pos {
+    x: f32,
+    y: f32,
+}
+
+fn main() {
+    #[rustfmt::skip]
+    let mut packet = [
+        0x45, 0x00, 0x00, 0x54, 0x12, 0x34, 0x40, 0x00,
+        0x40, 0x01, 0xab, 0xcd, 10, 0, 0, 1, 10, 0, 0, 2,
+    ];
+    let hdr: Be<Ipv4Header> = Be::new(&mut packet);
+    assert_eq!(p!(safe(*hdr).total_len), 84);
+    assert_eq!(p!(safe(*hdr).id), 0x1234);
+    assert_eq!(p!(safe(*hdr).dst[3]), 2);
+    p!(safe(*hdr).ttl = 63);
+    p!(safe(*hdr).checksum = 0xbeef);
+    // A sub-pointer keeps the byte order.
+    let flags: Be<u16> = p!(safe @_ (*hdr).frag);
+    assert_eq!(p!(safe * flags), 0x4000);
+    // Whole structs are converted field by field.
+    assert_eq!(p!(safe * hdr).checksum, 0xbeef);
+    assert_eq!(packet[8], 63);
+    assert_eq!(packet[10..12], [0xbe, 0xef]);
+
+    let mut file = [0u8; 12];
+    let sample: Le<Sample> = Le::new(&mut file);
+    p!(safe(*sample).tag = 0x0102_0304);
+    p!(safe(*sample).pos.y = 1.5);
+    assert_eq!(file[..4], [4, 3, 2, 1]);
+    assert_eq!(file[8..], 1.5f32.to_le_bytes());
+    let sample: Le<Sample> = Le::new(&mut file);
+    assert_eq!(
+        p!(safe * sample),
+        Sample {
+            tag: 0x0102_0304,
+            pos: Pos { x: 0.0, y: 1.5 }
+        }
+    );
+}
@@ -0,0 +1,101 @@
+#![feature(ptr_metadata)]
+#![allow(non_camel_case_types)]
+
+use std::ptr::NonNull;
+
+use place_projections::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+mk_field_proj!(struct x(Point.x: u32));
+mk_field_proj!(struct y(Point.y: u32));
+
+struct Polygon {
+    corners: [Point; 3],
+}
+mk_field_proj!(struct corners(Polygon.corners: [Point; 3]));
+
+/// A pointer to a `Vec`. Its elements don't live inside the place of the `Vec`, so indexing
+/// returns a pointer into the heap buffer instead of projecting.
+struct VecPtr<T>(NonNull<Vec<T>>);
+
+impl<T> HasPlace for VecPtr<T> {
+    type Target = Vec<T>;
+}
+
+unsafe impl<T> PlaceIndex<NoopProj<Vec<T>>, usize> for VecPtr<T> {
+    type Output = *mut T;
+    unsafe fn index(ptr: *const Self, _p: &NoopProj<Vec<T>>, idx: usize) -> *mut T {
+        unsafe {
+            let v = (*ptr).0.as_ptr();
+            assert!(idx < (*v).len(), "index out of bounds");
+            (*v).as_mut_ptr().add(idx)
+        }
+    }
+}
+
+/// Words that are indexed without bounds checks. `IndexProjection` is a safe trait, so its
+/// projection isn't trusted: only `SafeProjection`s can be used from safe code.
+struct Words([u64; 2]);
+
+impl IndexProjection<usize> for Words {
+    type Output = u64;
+    type Proj = SizedProj<Words, u64>;
+    fn index_proj(idx: usize) -> Self::Proj {
+        SizedProj::new(idx * size_of::<u64>())
+    }
+}
+
+fn main() {
+    let mut points = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+    let mut polygon = Polygon {
+        corners: [
+            Point { x: 0, y: 0 },
+            Point { x: 5, y: 0 },
+            Point { x: 0, y: 5 },
+        ],
+    };
+    let mut vec = vec![Point { x: 7, y: 8 }, Point { x: 9, y: 10 }];
+
+    unsafe {
+        // Slices.
+        let s: *mut [Point] = &raw mut points;
+        assert_eq!(p!((*s)[1].x), 3); // read at an index
+        p!((*s)[0].y = 20); // write at an index
+        assert_eq!(points[0].y, 20);
+        let ptr_y: *const u32 = p!(@_ (*s)[1].y); // borrow at an index
+        assert_eq!(*ptr_y, 4);
+        let sub: *mut [Point] = p!(@_ (*s)[1..2]); // borrow a subslice
+        assert_eq!(sub.len(), 1);
+        assert_eq!(p!((*sub)[0]), Point { x: 3, y: 4 });
+
+        // Arrays nested in a struct.
+        let poly: &Polygon = &polygon;
+        assert_eq!(p!((*poly).corners[2].y), 5);
+        let poly: *mut Polygon = &raw mut polygon;
+        p!((*poly).corners[1].y = 6);
+        assert_eq!(polygon.corners[1].y, 6);
+        let arr: *mut [Point; 3] = p!(@_ (*poly).corners);
+        let tail: *mut [Point] = p!(@_ (*arr)[1..3]); // borrow a subslice of an array
+        assert_eq!(tail.len(), 2);
+        assert_eq!(p!((*tail)[1].y), 5);
+
+        // A custom pointer whose elements live elsewhere.
+        let v = VecPtr(NonNull::from(&mut vec));
+        assert_eq!(p!((*v)[1].y), 10);
+        p!((*v)[0].x = 70);
+        assert_eq!(vec[0].x, 70);
+    }
+
+    // Indexing is bounds-checked so this is safe on references.
+    let poly: &Polygon = &polygon;
+    assert_eq!(p!(safe(*poly).corners[1].y), 6);
+
+    let words = &Words([1, 2]);
+    assert_eq!(unsafe { p!((*words)[1]) }, words.0[1]);
+    // Doesn't compile: `SizedProj` isn't a `SafeProjection`, so this could read out of bounds.
+    // p!(safe (*words)[4096]);
+}
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+
+use place_projections::*;
+
+#[derive(FieldProjections, NoUninit, Clone, Copy, PartialEq, Debug)]
+struct Creds {
+    user: u32,
+    pin: u32,
+}
+
+#[derive(FieldProjections, NoUninit, Clone, Copy)]
+struct Record {
+    id: u64,
+    creds: Creds,
+}
+
+#[derive(FieldProjections)]
+struct Node {
+    weight: u32,
+    next: &'static Record,
+}
+
+static TARGET: Record = Record {
+    id: 2,
+    creds: Creds { user: 5, pin: 0 },
+};
+
+fn main() {
+    let log = Rc::new(EventLog::new());
+
+    // Audit an existing pointer, here a borrow of an encrypted envelope.
+    let key = [7; 32];
+    let mut env = EncryptedEnvelope::<Record>::encrypt(
+        Record {
+            id: 1,
+            creds: Creds {
+                user: 10,
+                pin: 1234,
+            },
+        },
+        key,
+    );
+    let rec = Logged::new(env.borrow_mut(key), log.clone());
+    assert_eq!(p!(safe(*rec).creds.pin), 1234);
+    p!(safe(*rec).creds.user = 11);
+    let creds_ptr: Logged<EnvelopeBorrowMut<Record, Creds>> = unsafe { p!(@_ (*rec).creds) };
+    p!(safe(*creds_ptr).pin = 4321);
+    drop((rec, creds_ptr));
+    assert_eq!(
+        env.decrypt(key).creds,
+        Creds {
+            user: 11,
+            pin: 4321
+        }
+    );
+
+    let creds_at = std::mem::offset_of!(Record, creds);
+    let pin_at = creds_at + std::mem::offset_of!(Creds, pin);
+    let user_at = creds_at + std::mem::offset_of!(Creds, user);
+    let events = log.take();
+    let described: Vec<_> = events.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        described,
+        [
+            format!("Read creds.pin {:?}", pin_at..pin_at + 4),
+            format!("Write creds.user {:?}", user_at..user_at + 4),
+            format!("Borrow creds {:?} (Unique)", creds_at..creds_at + 8),
+            format!("Write creds.pin {:?}", pin_at..pin_at + 4),
+        ]
+    );
+    assert_eq!(events[2].borrow_kind, Some(BorrowKind::Unique));
+
+    // Any pointer works, e.g. references, and derefs are reported too. A closure can be the sink.
+    let node = Node {
+        weight: 3,
+        next: &TARGET,
+    };
+    let printed = Rc::new(std::cell::Cell::new(0));
+    let counter = printed.clone();
+    let sink = move |e: PlaceEvent| {
+        assert_eq!(e.path.to_string(), "next");
+        assert_eq!(e.op, PlaceOp::Deref);
+        counter.set(counter.get() + 1);
+    };
+    let n = Logged::new(&node, Rc::new(sink));
+    assert_eq!(p!(safe(*(*n).next).id), 2);
+    assert_eq!(printed.get(), 1);
+    let whole = Logged::new(&node, log.clone());
+    let r: Logged<*const u32> = p!(safe @_ (*whole).weight);
+    assert_eq!(unsafe { *r.into_inner() }, 3);
+    assert_eq!(log.events()[0].path.to_string(), "weight");
+}
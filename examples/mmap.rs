@@ -0,0 +1,74 @@
+use place_projections::*;
+
+/// An on-disk header. It's packed like many file formats, so most fields are misaligned.
+#[derive(Debug, Clone, Copy, PartialEq, FieldProjections)]
+#[repr(C, packed)]
+struct Header {
+    tag: u8,
+    version: u16,
+    entries: u32,
+}
+
+#[derive(Clone, Copy, FieldProjections)]
+#[repr(C, packed)]
+struct Entry {
+    id: u64,
+    len: u32,
+}
+
+fn main() -> std::io::Result<()> {
+    let path = std::env::temp_dir().join(format!("mmap-example-{}", std::process::id()));
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)?;
+    let map = {
+        file.set_len(4 * 4096)?;
+        Mmap::map(&file)?
+    };
+    let page = map.page_size();
+
+    let hdr: MmapPtr<Header> = map.at(1);
+    let table: MmapPtr<[Entry]> = map.slice_at(2 * page + 3, 10);
+    unsafe {
+        p!((*hdr).tag = 0x7f);
+        p!((*hdr).version = 3);
+        p!((*hdr).entries = 10);
+        p!((*table)[9].id = 99);
+        p!((*table)[9].len = 1234);
+        assert_eq!(
+            p!(*hdr),
+            Header {
+                tag: 0x7f,
+                version: 3,
+                entries: 10
+            }
+        );
+        assert_eq!(p!((*table)[9].len), 1234);
+    }
+    // Only the pages of the written projections are dirty.
+    let last = p!(safe @_ (*table)[9]);
+    let last_page = (last.offset() + size_of::<Entry>() - 1) / page;
+    let mut expected = vec![0, 2];
+    if last_page != 2 {
+        expected.push(last_page);
+    }
+    assert_eq!(map.dirty_pages(), expected);
+    map.flush()?;
+    assert!(map.dirty_pages().is_empty());
+
+    // The bytes made it to the file.
+    let bytes = std::fs::read(&path)?;
+    assert_eq!(bytes[1], 0x7f);
+    assert_eq!(u16::from_ne_bytes([bytes[2], bytes[3]]), 3);
+    let len_offset = last.offset() + 8;
+    assert_eq!(
+        u32::from_ne_bytes(bytes[len_offset..len_offset + 4].try_into().unwrap()),
+        1234
+    );
+
+    drop(map);
+    std::fs::remove_file(&path)
+}
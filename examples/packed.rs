@@ -0,0 +1,65 @@
+use std::ptr::NonNull;
+
+use place_projections::*;
+
+/// A packed wire header: `len` and `seq` are misaligned.
+#[derive(Debug, Clone, Copy, PartialEq, FieldProjections)]
+#[repr(C, packed)]
+struct Header {
+    tag: u8,
+    len: u32,
+    seq: u16,
+    body: Body,
+}
+
+/// Not packed, but can end up misaligned when embedded in a packed struct or a byte buffer.
+#[derive(Debug, Clone, Copy, PartialEq, FieldProjections)]
+#[repr(C)]
+struct Body {
+    a: u16,
+    b: u16,
+}
+
+fn main() {
+    let mut header = Header {
+        tag: 1,
+        len: 0xdead_beef,
+        seq: 7,
+        body: Body { a: 1, b: 2 },
+    };
+    let hdr = UnalignedPtr::new(NonNull::from(&mut header));
+    unsafe {
+        assert_eq!(p!((*hdr).len), 0xdead_beef);
+        p!((*hdr).seq = 8);
+        p!((*hdr).body.b = 3);
+        assert_eq!(p!((*hdr).body), Body { a: 1, b: 3 });
+
+        // `Header` itself has alignment 1, so it can always be borrowed.
+        let r: &Header = p!(@&_ *hdr);
+        assert_eq!({ r.seq }, 8);
+
+        // Doesn't compile: `len` is a field of a packed struct.
+        // let r: &u32 = p!(@&_ (*hdr).len);
+    }
+
+    // A struct at an odd offset in a buffer.
+    let mut buf = [0u16; 8];
+    let bytes = NonNull::from(&mut buf).cast::<u8>();
+    let in_buf = UnalignedPtr::new(unsafe { bytes.add(3) }.cast::<Body>());
+    unsafe {
+        p!((*in_buf).a = 0x0102);
+        p!((*in_buf).b = 0x0304);
+        assert_eq!(p!((*in_buf).b), 0x0304);
+
+        // `Body`'s fields are aligned relative to `Body`, so the projection is allowed, but this
+        // `Body` is misaligned. The borrow checks the address at runtime.
+        std::panic::set_hook(Box::new(|_| {}));
+        let res = std::panic::catch_unwind(|| {
+            let _: &u16 = p!(@&_ (*in_buf).a);
+        });
+        assert!(res.is_err());
+        let _ = std::panic::take_hook();
+    }
+    let buf_bytes: [u8; 16] = unsafe { std::mem::transmute(buf) };
+    assert_eq!(u16::from_ne_bytes([buf_bytes[3], buf_bytes[4]]), 0x0102);
+}
@@ -0,0 +1,76 @@
+use place_projections::*;
+
+/// `next` comes first, so a node that links to itself has a `RelPtr` that points to itself.
+#[derive(FieldProjections)]
+#[repr(C)]
+struct Node {
+    next: RelPtr<Node>,
+    value: u32,
+}
+
+/// A linked list that lives entirely in one block of memory, like a shared memory segment.
+#[derive(FieldProjections)]
+#[repr(C)]
+struct Region {
+    head: RelPtr<Node>,
+    nodes: [Node; 3],
+}
+
+/// Sum the values of the list, following the relative pointers.
+fn sum(region: *const Region) -> u32 {
+    let mut total = 0;
+    let mut node: *const Node = unsafe { p!(@*const _ *(*region).head) };
+    loop {
+        total += unsafe { p!((*node).value) };
+        if unsafe { (*node).next.is_null() } {
+            return total;
+        }
+        node = unsafe { p!(@*const _ *(*node).next) };
+    }
+}
+
+fn main() {
+    let mut region = Box::new(Region {
+        head: RelPtr::null(),
+        nodes: [1, 2, 3].map(|v| Node {
+            value: v,
+            next: RelPtr::null(),
+        }),
+    });
+    // Link head -> nodes[0] -> nodes[2].
+    region.head.set(&region.nodes[0]);
+    region.nodes[0].next.set(&region.nodes[2]);
+    let reg: *mut Region = &raw mut *region;
+    unsafe {
+        assert_eq!(p!((*(*reg).head).value), 1);
+        assert_eq!(p!((*(*(*reg).head).next).value), 3);
+        // Writing a pointer stores the offset from where it is written: splice nodes[1] in.
+        let old_next = p!((*(*reg).head).next);
+        let target = RelPtr::to(&raw const (*reg).nodes[1]);
+        p!((*(*reg).head).next = target);
+        p!((*(*(*reg).head).next).next = old_next);
+        p!((*(*(*reg).head).next).value = 20);
+    }
+    assert_eq!(sum(&*region), 1 + 20 + 3);
+    assert_eq!(region.nodes[1].value, 20);
+
+    // Move the whole region somewhere else: the links still work.
+    let moved: Box<Region> = Box::new(*region);
+    assert_eq!(sum(&*moved), 24);
+    let reg: *const Region = &*moved;
+    assert_eq!(unsafe { p!((*(*(*(*reg).head).next).next).value) }, 3);
+
+    // A pointer to itself isn't null.
+    let ring: *mut Node = Box::into_raw(Box::new(Node {
+        next: RelPtr::null(),
+        value: 7,
+    }));
+    unsafe {
+        (*ring).next.set(ring);
+        assert!(!(*ring).next.is_null());
+        p!((*(*ring).next).next = RelPtr::to(ring)); // rewrites itself
+        assert!(!(*ring).next.is_null());
+        assert_eq!(p!((*(*(*ring).next).next).value), 7);
+        drop(Box::from_raw(ring));
+    }
+}
@@ -0,0 +1,146 @@
+#![feature(ptr_metadata)]
+#![allow(non_camel_case_types)]
+
+use std::{
+    cell::Cell,
+    io,
+    rc::Rc,
+    sync::mpsc::{Sender, channel},
+    thread,
+};
+
+use place_projections::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, NoUninit)]
+#[repr(C)]
+struct Header {
+    magic: u32,
+    count: u32,
+}
+#[derive(Clone, Copy, NoUninit)]
+#[repr(C)]
+struct Record {
+    header: Header,
+    values: [u64; 512],
+}
+mk_field_proj!(struct header(Record.header: Header));
+mk_field_proj!(struct values(Record.values: [u64; 512]));
+mk_field_proj!(struct count(Header.count: u32));
+
+/// Wraps a store to count the bytes that go through it.
+struct Counting<S> {
+    inner: S,
+    read: Cell<usize>,
+}
+impl<S: ByteStore> ByteStore for Counting<S> {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.read.set(self.read.get() + buf.len());
+        self.inner.read(addr, buf)
+    }
+    fn write(&self, addr: u64, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write(addr, bytes)
+    }
+}
+
+enum Request {
+    Read(u64, usize, Sender<Vec<u8>>),
+    Write(u64, Vec<u8>),
+}
+
+/// A client for a mock server running in another thread, which keeps the bytes in a `MemStore`.
+struct MockServer(Sender<Request>);
+impl MockServer {
+    fn spawn() -> Self {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let store = MemStore::default();
+            for req in rx {
+                match req {
+                    Request::Read(addr, len, reply) => {
+                        let mut buf = vec![0; len];
+                        store.read(addr, &mut buf).unwrap();
+                        let _ = reply.send(buf);
+                    }
+                    Request::Write(addr, bytes) => store.write(addr, &bytes).unwrap(),
+                }
+            }
+        });
+        Self(tx)
+    }
+}
+fn disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "mock server is gone")
+}
+impl ByteStore for MockServer {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        let (tx, rx) = channel();
+        self.0
+            .send(Request::Read(addr, buf.len(), tx))
+            .map_err(|_| disconnected())?;
+        buf.copy_from_slice(&rx.recv().map_err(|_| disconnected())?);
+        Ok(())
+    }
+    fn write(&self, addr: u64, bytes: &[u8]) -> io::Result<()> {
+        self.0
+            .send(Request::Write(addr, bytes.to_vec()))
+            .map_err(|_| disconnected())
+    }
+}
+
+/// The same operations work on any store.
+fn exercise(store: Rc<dyn ByteStore>) {
+    let rec: RemotePtr<Record> = RemotePtr::new(store, 10_000);
+    // Writes send all the bytes of the value, so only `NoUninit` types without padding can be
+    // written.
+    p!(safe @_ (*rec).header)
+        .try_write(Header {
+            magic: 0xC0FFEE,
+            count: 0,
+        })
+        .unwrap();
+    unsafe {
+        p!((*rec).header.count = 3);
+        p!((*rec).values[511] = 42);
+        assert_eq!(p!((*rec).header.count), 3);
+        assert_eq!(p!((*rec).values[511]), 42);
+        assert_eq!(p!((*rec).values[0]), 0);
+    }
+    // Borrowing only computes addresses.
+    let last: RemotePtr<u64> = p!(safe @_ (*rec).values[511]);
+    assert_eq!(last.addr(), 10_000 + 8 + 511 * 8);
+    let vals: RemotePtr<[u64]> = p!(safe @RemotePtr (*rec).values).unsize();
+    let tail: RemotePtr<[u64]> = p!(safe @_ (*vals)[500..512]);
+    assert_eq!(tail.metadata(), 12);
+    assert_eq!(unsafe { p!((*tail)[11]) }, 42);
+}
+
+fn main() {
+    exercise(Rc::new(MemStore::default()));
+
+    let path = std::env::temp_dir().join(format!("remote-example-{}", std::process::id()));
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    file.set_len(20_000).unwrap();
+    exercise(Rc::new(FileStore(file)));
+    std::fs::remove_file(&path).unwrap();
+
+    exercise(Rc::new(MockServer::spawn()));
+
+    // Reading a field only fetches the bytes of that field.
+    let store = Rc::new(Counting {
+        inner: MemStore::default(),
+        read: Cell::new(0),
+    });
+    let rec: RemotePtr<Record> = RemotePtr::new(store.clone(), 0);
+    unsafe {
+        let _ = p!((*rec).header.count);
+        assert_eq!(store.read.get(), 4);
+        let _ = p!((*rec).header);
+        assert_eq!(store.read.get(), 4 + 8);
+    }
+}
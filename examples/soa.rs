@@ -0,0 +1,68 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, Copy, PartialEq, Debug)]
+struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+#[derive(FieldProjections, Soa)]
+struct Particle {
+    pos: Vec2,
+    vel: Vec2,
+    age: u32,
+    alive: bool,
+}
+
+fn main() {
+    let mut particles: SoaVec<Particle> = (0..4)
+        .map(|i| Particle {
+            pos: Vec2 {
+                x: i as f32,
+                y: 0.0,
+            },
+            vel: Vec2 { x: 1.0, y: 2.0 },
+            age: 0,
+            alive: true,
+        })
+        .collect();
+
+    // Borrowing a field gives a reference into that field's column.
+    let r = particles.get(2).unwrap();
+    let pos_ref: &Vec2 = p!(safe @_ (*r).pos);
+    assert_eq!(*pos_ref, Vec2 { x: 2.0, y: 0.0 });
+    assert!(std::ptr::eq(pos_ref, &particles.column(pos)[2]));
+    assert!(p!(safe(*r).alive));
+    // Places inside a field are projected with their offset inside of it.
+    let vy: &f32 = p!(safe @_ (*r).vel.y);
+    assert!(std::ptr::eq(vy, &particles.column(vel)[2].y));
+
+    // Update through mutable element pointers.
+    for i in 0..particles.len() {
+        let m = particles.get_mut(i).unwrap();
+        let v = p!(safe(*m).vel);
+        let px: &mut f32 = unsafe { p!(@&mut _ (*m).pos.x) };
+        *px += v.x;
+        p!(safe(*m).pos.y = v.y);
+        p!(safe(*m).age = i as u32);
+        p!(safe(*m).alive = i % 2 == 0);
+    }
+    assert_eq!(
+        particles.column(pos),
+        [0.0, 1.0, 2.0, 3.0].map(|px| Vec2 {
+            x: px + 1.0,
+            y: 2.0
+        })
+    );
+    assert_eq!(particles.column(age), [0, 1, 2, 3]);
+    particles.column_mut(age).iter_mut().for_each(|a| *a += 10);
+
+    // Columns stay in sync when elements are removed.
+    let removed = particles.swap_remove(1);
+    assert!(!removed.alive && removed.age == 11);
+    assert_eq!(particles.column(age), [10, 13, 12]);
+    assert_eq!(particles.column(alive), [true, false, true]);
+    let last = particles.get(1).unwrap();
+    assert_eq!(p!(safe(*last).pos.x), 4.0);
+    assert!(particles.get(3).is_none());
+}
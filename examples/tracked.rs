@@ -0,0 +1,66 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, Copy)]
+struct Size {
+    w: u32,
+    h: u32,
+}
+
+#[derive(FieldProjections)]
+struct Widget {
+    size: Size,
+    colors: [u32; 4],
+    visible: bool,
+    label: String,
+}
+
+fn path(segments: &[PathSegment]) -> PlacePath {
+    PlacePath(segments.to_vec())
+}
+
+fn main() {
+    let mut widget = Widget {
+        size: Size { w: 10, h: 20 },
+        colors: [0; 4],
+        visible: true,
+        label: "ok".to_owned(),
+    };
+    let tracker = Tracker::new();
+    let t = tracker.track(&mut widget);
+    assert!(!tracker.is_dirty());
+
+    // Reads don't count as changes.
+    assert_eq!(p!(safe(*t).size.w), 10);
+    assert!(!tracker.is_dirty());
+
+    p!(safe(*t).size.h = 25);
+    p!(safe(*t).colors[2] = 0xff0000);
+    p!(safe(*t).visible = false);
+    p!(safe(*t).visible = true);
+    // Borrowed pointers record into the same tracker, with their full path.
+    let sz: Tracked<Size> = p!(safe @_ (*t).size);
+    assert_eq!(sz.path().to_string(), "size");
+    p!(safe(*sz).w = 11);
+    unsafe { p!((*t).label = "changed".to_owned()) };
+
+    let changes = tracker.changes();
+    let paths: Vec<_> = changes.iter().map(|c| c.path.to_string()).collect();
+    assert_eq!(paths, ["colors[2]", "label", "size.h", "size.w", "visible"]);
+    let h_change = &changes[2];
+    let h_offset = std::mem::offset_of!(Widget, size) + std::mem::offset_of!(Size, h);
+    assert_eq!(h_change.bytes, h_offset..h_offset + 4);
+
+    // Queries by path match places inside and around the written ones.
+    use PathSegment::*;
+    assert!(tracker.is_changed(&path(&[Field("size")])));
+    assert!(tracker.is_changed(&path(&[Field("colors"), Index(2)])));
+    assert!(!tracker.is_changed(&path(&[Field("colors"), Index(1)])));
+    assert!(tracker.is_changed(&PlacePath::of(&colors)));
+    assert!(tracker.overlaps(h_offset..h_offset + 1));
+
+    assert_eq!(tracker.take().len(), 5);
+    assert!(!tracker.is_dirty());
+    assert_eq!(widget.size.w, 11);
+    assert_eq!(widget.colors, [0, 0, 0xff0000, 0]);
+    assert_eq!(widget.label, "changed");
+}
@@ -0,0 +1,79 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, Copy, PartialEq, Debug)]
+struct Limits {
+    daily: u32,
+    single: u32,
+}
+
+#[derive(FieldProjections, Clone, PartialEq, Debug)]
+struct Account {
+    balance: i64,
+    limits: Limits,
+    recent: [i64; 4],
+    owner: String,
+}
+
+fn transfer(acc: TxPtr<Account>, amount: i64) -> Result<(), &'static str> {
+    let bal = p!(safe(*acc).balance);
+    p!(safe(*acc).balance = bal - amount);
+    p!(safe(*acc).recent[0] = -amount);
+    if amount > i64::from(p!(safe(*acc).limits.single)) {
+        return Err("over the limit");
+    }
+    Ok(())
+}
+
+fn main() {
+    let original = Account {
+        balance: 100,
+        limits: Limits {
+            daily: 500,
+            single: 50,
+        },
+        recent: [0; 4],
+        owner: "ann".to_owned(),
+    };
+    let mut account = original.clone();
+
+    // Nested and overlapping writes are undone in reverse order, restoring the oldest bytes.
+    let tx = Tx::new(&mut account);
+    let acc = tx.ptr();
+    p!(safe(*acc).limits.daily = 1);
+    p!(safe(*acc).limits = Limits {
+        daily: 2,
+        single: 3
+    });
+    let lim: TxPtr<Limits> = p!(safe @_ (*acc).limits);
+    p!(safe(*lim).single = 4);
+    p!(safe(*acc).recent[1] = 7);
+    p!(safe(*acc).recent = [9; 4]);
+    assert_eq!(
+        p!(safe(*acc).limits),
+        Limits {
+            daily: 2,
+            single: 4
+        }
+    );
+    assert_eq!(tx.log_len(), 5);
+    let restored = tx.rollback();
+    assert_eq!(*restored, original);
+
+    // Committed writes stay.
+    let tx = Tx::new(&mut account);
+    transfer(tx.ptr(), 30).unwrap();
+    let committed = tx.commit();
+    assert_eq!(committed.balance, 70);
+    assert_eq!(committed.recent, [-30, 0, 0, 0]);
+
+    // A transaction dropped without commit, e.g. on an error, is rolled back.
+    let before = account.clone();
+    {
+        let tx = Tx::new(&mut account);
+        assert!(transfer(tx.ptr(), 60).is_err());
+        let acc = tx.ptr();
+        assert_eq!(p!(safe(*acc).balance), 10);
+    }
+    assert_eq!(account, before);
+    assert_eq!(account.owner, "ann");
+}
@@ -0,0 +1,66 @@
+use place_projections::*;
+
+#[derive(FieldProjections, Clone, Copy, PartialEq, Debug)]
+struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+#[derive(FieldProjections, Clone, Copy, PartialEq, Debug)]
+struct Body {
+    pos: Vec2,
+    vel: Vec2,
+    steps: u32,
+}
+
+fn step(b: &Versioned<Body>) {
+    let v = p!(safe(**b).vel);
+    let pos_now = p!(safe(**b).pos);
+    p!(safe(**b).pos = Vec2 {
+        x: pos_now.x + v.x,
+        y: pos_now.y + v.y,
+    });
+    let n = p!(safe(**b).steps);
+    p!(safe(**b).steps = n + 1);
+}
+
+fn main() {
+    let start = Body {
+        pos: Vec2 { x: 0.0, y: 0.0 },
+        vel: Vec2 { x: 1.0, y: 0.5 },
+        steps: 0,
+    };
+    let body = Versioned::new(start);
+    step(&body); // versions 1, 2
+    p!(safe(*body).vel.y = -1.0); // version 3
+    step(&body); // versions 4, 5
+    assert_eq!(body.version(), 5);
+    assert_eq!(p!(safe(*body).pos), Vec2 { x: 2.0, y: -0.5 });
+
+    // Any place can be read at any version, including places inside or around written ones.
+    let pos_y = pos.compose(y);
+    assert_eq!(body.read_at(&pos_y, 0), 0.0);
+    assert_eq!(body.read_at(&pos_y, 1), 0.5);
+    assert_eq!(body.read_at(&pos_y, 5), -0.5);
+    assert_eq!(body.read_at(&vel, 2), Vec2 { x: 1.0, y: 0.5 });
+    assert_eq!(body.read_at(&vel, 3), Vec2 { x: 1.0, y: -1.0 });
+    assert_eq!(body.read_at(&vel.compose(x), 4), 1.0);
+    assert_eq!(body.read_at(&steps, 3), 1);
+    assert_eq!(body.value_at(0), start);
+    assert_eq!(
+        body.value_at(3),
+        Body {
+            pos: Vec2 { x: 1.0, y: 0.5 },
+            vel: Vec2 { x: 1.0, y: -1.0 },
+            steps: 1,
+        }
+    );
+
+    let revisions = body.revisions();
+    let paths: Vec<_> = revisions.iter().map(|r| r.path.to_string()).collect();
+    assert_eq!(paths, ["pos", "steps", "vel.y", "pos", "steps"]);
+    assert_eq!(revisions[2].version, 3);
+    let vel_y = std::mem::offset_of!(Body, vel) + std::mem::offset_of!(Vec2, y);
+    assert_eq!(revisions[2].bytes, vel_y..vel_y + 4);
+    assert_eq!(body.into_inner().steps, 2);
+}
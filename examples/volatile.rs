@@ -0,0 +1,48 @@
+use std::ptr::NonNull;
+
+use place_projections::*;
+
+/// A made-up UART.
+#[derive(FieldProjections)]
+#[repr(C)]
+struct Uart {
+    ctrl: Ctrl,
+    status: ReadOnly<u32>,
+    tx: WriteOnly<u32>,
+    scratch: [u32; 2],
+}
+
+#[derive(FieldProjections)]
+#[repr(C)]
+struct Ctrl {
+    enable: u32,
+    baud: u32,
+}
+
+fn main() {
+    // A plain buffer stands in for the device registers.
+    let mut mem = [0u32; 6];
+    mem[2] = 0x80; // status: ready
+    let base = NonNull::from(&mut mem).cast::<Uart>();
+    let uart = unsafe { VolatilePtr::new(base) };
+
+    unsafe {
+        p!((*uart).ctrl.enable = 1);
+        p!((*uart).ctrl.baud = 115_200);
+        assert_eq!(p!((*uart).status), 0x80);
+        p!((*uart).tx = u32::from(b'!'));
+        p!((*uart).scratch[1] = 7);
+        assert_eq!(p!((*uart).scratch[1]), 7);
+
+        // Borrowing a read-write subplace gives another volatile pointer.
+        let c: VolatilePtr<Ctrl> = p!(@VolatilePtr (*uart).ctrl);
+        assert_eq!(p!((*c).baud), 115_200);
+
+        // These don't compile:
+        // p!((*uart).status = 0); // `status` projects to a read-only place
+        // p!((*uart).tx); // `tx` projects to a write-only place
+        // p!(@VolatilePtr (*uart).status); // would allow writing to `status`
+    }
+    let mem = unsafe { base.cast::<[u32; 6]>().read() };
+    assert_eq!(mem, [1, 115_200, 0x80, 0x21, 0, 7]);
+}
@@ -0,0 +1,547 @@
+//! `#[derive(FieldProjections)]`: one projection per field, like `mk_field_proj!` but with access
+//! to the struct definition. Also `#[derive(EndianConvert)]`, `#[derive(NoUninit)]`,
+//! `#[derive(Validate)]` and `#[derive(Soa)]`.
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitInt, Path,
+    PathArguments, Result, Token, Type, ext::IdentExt, parenthesized, parse::ParseStream,
+    parse_quote, spanned::Spanned, token,
+};
+
+/// The bit range of a `#[bits(a..b)]` field, if it has one.
+fn bit_range(attrs: &[Attribute]) -> Result<Option<(LitInt, LitInt)>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("bits")) else {
+        return Ok(None);
+    };
+    attr.parse_args_with(|input: ParseStream| {
+        let start = input.parse()?;
+        input.parse::<Token![..]>()?;
+        let end = input.parse()?;
+        Ok(Some((start, end)))
+    })
+}
+
+/// Recognize `Bits<T>` fields and return `T`.
+fn bits_target(ty: &Type) -> Option<&Type> {
+    if let Type::Path(path) = ty
+        && path.qself.is_none()
+        && let Some(last) = path.path.segments.last()
+        && last.ident == "Bits"
+        && let PathArguments::AngleBracketed(args) = &last.arguments
+        && args.args.len() == 1
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        return Some(inner);
+    }
+    None
+}
+
+/// Which place operations a field allows.
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    ReadWrite,
+    ReadOnly,
+    WriteOnly,
+}
+
+/// Recognize `ReadOnly<T>` and `WriteOnly<T>` fields. Returns the type the projection should
+/// target, i.e. `T` for wrapped fields.
+fn field_access(ty: &Type) -> (Access, &Type) {
+    if let Type::Path(path) = ty
+        && path.qself.is_none()
+        && let Some(last) = path.path.segments.last()
+        && let PathArguments::AngleBracketed(args) = &last.arguments
+        && args.args.len() == 1
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        if last.ident == "ReadOnly" {
+            return (Access::ReadOnly, inner);
+        } else if last.ident == "WriteOnly" {
+            return (Access::WriteOnly, inner);
+        }
+    }
+    (Access::ReadWrite, ty)
+}
+
+/// The parts of a `#[repr(..)]` attribute we care about.
+#[derive(Default)]
+struct Repr {
+    c: bool,
+    /// `packed` or `packed(N)`.
+    packed: bool,
+    /// The integer type of an enum, e.g. `u8`.
+    int: Option<Ident>,
+}
+
+fn parse_repr(attrs: &[Attribute]) -> Result<Repr> {
+    const INTS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    let mut repr = Repr::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            repr.c |= meta.path.is_ident("C");
+            repr.packed |= meta.path.is_ident("packed");
+            if let Some(ident) = meta.path.get_ident()
+                && INTS.iter().any(|int| ident == int)
+            {
+                repr.int = Some(ident.clone());
+            }
+            // Skip the arguments of `packed(N)`, `align(N)`, etc.
+            if meta.input.peek(token::Paren) {
+                let args;
+                parenthesized!(args in meta.input);
+                args.parse::<TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr)
+}
+
+/// The length field of a `#[len(field)]` slice tail, if it has one.
+fn len_field(attrs: &[Attribute]) -> Result<Option<Ident>> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("len"))
+        .map(|attr| attr.parse_args())
+        .transpose()
+}
+
+/// The path of the `place-projections` crate: `::place_projections`, or `path` given with
+/// `#[place_projections(crate = path)]`, e.g. when the dependency is renamed.
+fn crate_path(attrs: &[Attribute]) -> Result<Path> {
+    let mut krate = parse_quote!(::place_projections);
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("place_projections"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.call(Path::parse_mod_style)?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `crate = path`"))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+/// `NamedProjection` for the projection to field `name`.
+fn named_projection(krate: &Path, name: &Ident) -> TokenStream {
+    let field = name.unraw().to_string();
+    quote_spanned! {name.span()=>
+        impl #krate::NamedProjection for #name {
+            fn push_path(&self, path: &mut #krate::PlacePath) {
+                path.push(#krate::PathSegment::Field(#field));
+            }
+        }
+    }
+}
+
+pub fn field_projections(input: DeriveInput) -> Result<TokenStream> {
+    let src = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            src,
+            "`FieldProjections` can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`FieldProjections` doesn't support generic structs",
+        ));
+    }
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "`FieldProjections` requires named fields",
+        ));
+    };
+
+    // Fields of packed structs may be misaligned. We can't check alignments here, so we treat
+    // them all as misaligned.
+    let repr = parse_repr(&input.attrs)?;
+    let packed = repr.packed;
+
+    let krate = crate_path(&input.attrs)?;
+    let mut out = TokenStream::new();
+
+    // A struct ending in a slice is unsized, with the length of the slice as metadata.
+    let tail = match fields.named.last().map(|field| &field.ty) {
+        Some(Type::Slice(slice)) => Some(&slice.elem),
+        _ => None,
+    };
+    let meta = if tail.is_some() {
+        quote!(usize)
+    } else {
+        quote!(())
+    };
+    if let Some(elem) = tail {
+        if !repr.c || packed {
+            return Err(Error::new_spanned(
+                src,
+                "`FieldProjections` requires `#[repr(C)]` and no `packed` for structs ending in a slice",
+            ));
+        }
+        let last = fields.named.last().unwrap();
+        let name = last.ident.as_ref().unwrap();
+        let vis = &last.vis;
+        out.extend(named_projection(&krate, name));
+        // With `#[repr(C)]`, the slice comes right after the previous field, aligned for its
+        // elements.
+        let prefix_end = match fields.named.iter().nth_back(1) {
+            Some(prev) => {
+                let (prev_name, prev_ty) = (prev.ident.as_ref().unwrap(), &prev.ty);
+                quote!(::core::mem::offset_of!(#src, #prev_name) + ::core::mem::size_of::<#prev_ty>())
+            }
+            None => quote!(0),
+        };
+        out.extend(quote_spanned! {name.span()=>
+            #[derive(Clone)]
+            #[allow(non_camel_case_types)]
+            #vis struct #name;
+            impl #name {
+                const OFFSET: usize = (#prefix_end).next_multiple_of(::core::mem::align_of::<#elem>());
+            }
+            unsafe impl #krate::SafeProjection for #name {}
+            unsafe impl #krate::AlignedProjection for #name {}
+            impl #krate::ReadableProjection for #name {}
+            impl #krate::WritableProjection for #name {}
+            impl #krate::Projection for #name {
+                type Source = #src;
+                type Target = [#elem];
+                fn offset(&self, _: usize) -> usize {
+                    Self::OFFSET
+                }
+                fn project_metadata(&self, len: usize) -> usize {
+                    len
+                }
+            }
+            unsafe impl #krate::ByteLen for #src {
+                fn byte_len(len: usize) -> ::core::option::Option<usize> {
+                    #krate::slice_tail_byte_len::<#src, #elem>(#name::OFFSET, len)
+                }
+            }
+        });
+        if let Some(len) = len_field(&last.attrs)? {
+            out.extend(quote_spanned! {len.span()=>
+                impl #krate::SliceTail for #src {
+                    type Len = <#len as #krate::Projection>::Target;
+                    type LenField = #len;
+                    const LEN_FIELD: #len = #len;
+                }
+            });
+        }
+    }
+    let sized_fields = fields
+        .named
+        .iter()
+        .take(fields.named.len() - tail.is_some() as usize);
+    // The field that holds the following bit fields.
+    let mut storage = None;
+    for (index, field) in sized_fields.enumerate() {
+        let name = field.ident.as_ref().unwrap();
+        let vis = &field.vis;
+        out.extend(named_projection(&krate, name));
+        if len_field(&field.attrs)?.is_some() {
+            return Err(Error::new_spanned(
+                name,
+                "`#[len(..)]` goes on a slice as the last field",
+            ));
+        }
+        if let Some((start, end)) = bit_range(&field.attrs)? {
+            let Some(target) = bits_target(&field.ty) else {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`#[bits(..)]` fields must have type `Bits<T>`",
+                ));
+            };
+            let Some((storage_name, storage_ty)) = storage else {
+                return Err(Error::new_spanned(
+                    name,
+                    "a `#[bits(..)]` field must come after the field that holds its bits",
+                ));
+            };
+            let ty = &field.ty;
+            // A constant instead of a unit struct, so that the projection can hold the marker
+            // that opts it out of `ByteProjection`.
+            out.extend(quote_spanned! {name.span()=>
+                #[derive(Clone)]
+                #[allow(non_camel_case_types)]
+                #vis struct #name {
+                    _marker: #krate::BitFieldMarker,
+                }
+                #[allow(non_upper_case_globals)]
+                #vis const #name: #name = #name {
+                    _marker: #krate::BitFieldMarker,
+                };
+                const _: fn(#ty) -> #krate::Bits<#target> = ::core::convert::identity;
+                const _: () = {
+                    let (start, end): (u32, u32) = (#start, #end);
+                    assert!(
+                        start < end
+                            && end <= <#storage_ty as #krate::BitValue>::BITS
+                            && end - start <= <#target as #krate::BitValue>::BITS,
+                        "invalid bit range"
+                    );
+                };
+                unsafe impl #krate::BitProjection for #name {
+                    type Storage = #storage_ty;
+                    fn bit_range(&self) -> ::core::ops::Range<u32> {
+                        #start..#end
+                    }
+                }
+                impl #krate::Projection for #name {
+                    type Source = #src;
+                    type Target = #target;
+                    fn offset(&self, _: #meta) -> usize {
+                        ::core::mem::offset_of!(#src, #storage_name)
+                    }
+                    fn project_metadata(&self, _: #meta) {}
+                }
+            });
+            continue;
+        }
+        storage = Some((name, &field.ty));
+        let (access, target) = field_access(&field.ty);
+        out.extend(quote_spanned! {name.span()=>
+            #[derive(Clone)]
+            #[allow(non_camel_case_types)]
+            #vis struct #name;
+            unsafe impl #krate::SafeProjection for #name {}
+            impl #krate::Projection for #name {
+                type Source = #src;
+                type Target = #target;
+                fn offset(&self, _: #meta) -> usize {
+                    ::core::mem::offset_of!(#src, #name)
+                }
+                fn project_metadata(&self, _: #meta) {}
+            }
+        });
+        if tail.is_none() {
+            out.extend(quote_spanned! {name.span()=>
+                unsafe impl #krate::FieldIndex for #name {
+                    const INDEX: usize = #index;
+                }
+                impl #name {
+                    /// Const version of `ProjectionExt::as_sized`.
+                    #[allow(dead_code)]
+                    #vis const fn as_sized(&self) -> #krate::SizedProj<#src, #target> {
+                        #krate::SizedProj::new(::core::mem::offset_of!(#src, #name))
+                    }
+                }
+            });
+        }
+        if !packed {
+            out.extend(quote! {
+                unsafe impl #krate::AlignedProjection for #name {}
+            });
+        }
+        let ty = &field.ty;
+        match access {
+            Access::ReadWrite => out.extend(quote! {
+                impl #krate::ReadableProjection for #name {}
+                impl #krate::WritableProjection for #name {}
+            }),
+            // Make sure the wrapper is ours, so that `T` really is at the field offset.
+            Access::ReadOnly => out.extend(quote_spanned! {ty.span()=>
+                const _: fn(#ty) -> #krate::ReadOnly<#target> = ::core::convert::identity;
+                impl #krate::ReadableProjection for #name {}
+            }),
+            Access::WriteOnly => out.extend(quote_spanned! {ty.span()=>
+                const _: fn(#ty) -> #krate::WriteOnly<#target> = ::core::convert::identity;
+                impl #krate::WritableProjection for #name {}
+            }),
+        }
+    }
+    Ok(out)
+}
+
+pub fn endian_convert(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "`EndianConvert` can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`EndianConvert` doesn't support generic structs",
+        ));
+    }
+    let krate = crate_path(&input.attrs)?;
+    let tys = data.fields.iter().map(|field| &field.ty);
+    // Fields are read by copy, which works for packed structs too.
+    let swapped = data
+        .fields
+        .members()
+        .map(|member| quote!(#member: #krate::EndianConvert::swap_bytes(self.#member)));
+    Ok(quote! {
+        const _: () = assert!(
+            ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#tys>())*,
+            "`EndianConvert` types can't have padding"
+        );
+        unsafe impl #krate::EndianConvert for #name {
+            fn swap_bytes(self) -> Self {
+                Self { #(#swapped),* }
+            }
+        }
+    })
+}
+
+pub fn no_uninit(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "`NoUninit` can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`NoUninit` doesn't support generic structs",
+        ));
+    }
+    let krate = crate_path(&input.attrs)?;
+    let tys: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    Ok(quote! {
+        const _: () = assert!(
+            ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#tys>())*,
+            "`NoUninit` types can't have padding"
+        );
+        unsafe impl #krate::NoUninit for #name where #(#tys: #krate::NoUninit,)* {}
+    })
+}
+
+pub fn validate(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`Validate` doesn't support generic types",
+        ));
+    }
+    let krate = crate_path(&input.attrs)?;
+    let check = match &input.data {
+        // Each field must be valid; padding can be anything.
+        Data::Struct(data) => {
+            let checks = data.fields.members().zip(&data.fields).map(|(member, field)| {
+                let ty = &field.ty;
+                quote! {
+                    <#ty as #krate::Validate>::validate(
+                        &bytes[::core::mem::offset_of!(Self, #member)..][..::core::mem::size_of::<#ty>()]
+                    )
+                }
+            });
+            quote!(#(&& #checks)*)
+        }
+        // The discriminant must be the one of a variant.
+        Data::Enum(data) => {
+            let Some(int) = parse_repr(&input.attrs)?.int else {
+                return Err(Error::new_spanned(
+                    name,
+                    "`Validate` requires enums to have an integer repr, e.g. `#[repr(u8)]`",
+                ));
+            };
+            if let Some(variant) = data.variants.iter().find(|v| !v.fields.is_empty()) {
+                return Err(Error::new_spanned(
+                    variant,
+                    "`Validate` only supports enums without fields",
+                ));
+            }
+            let variants = data.variants.iter().map(|v| &v.ident);
+            quote! {
+                && {
+                    let d = <#int>::from_ne_bytes(bytes.try_into().unwrap());
+                    false #(|| d == Self::#variants as #int)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                name,
+                "`Validate` can't be derived for unions",
+            ));
+        }
+    };
+    Ok(quote! {
+        unsafe impl #krate::Validate for #name {
+            fn validate(bytes: &[u8]) -> bool {
+                bytes.len() == ::core::mem::size_of::<Self>() #check
+            }
+        }
+    })
+}
+
+pub fn soa(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "`Soa` can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`Soa` doesn't support generic structs",
+        ));
+    }
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "`Soa` requires named fields",
+        ));
+    };
+    let krate = crate_path(&input.attrs)?;
+    let vis = &input.vis;
+    let columns = format_ident!("{name}Columns");
+    let names: Vec<_> = fields
+        .named
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect();
+    let tys = fields.named.iter().map(|f| &f.ty);
+    let indices: Vec<_> = (0..names.len()).collect();
+    // Local names that can't clash with the unit structs of `FieldProjections`.
+    let [cols, value, index, field] =
+        ["columns", "value", "index", "field"].map(|name| Ident::new(name, Span::mixed_site()));
+    let doc = format!("The columns of a `SoaVec<{name}>`, one `Vec` per field.");
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Default)]
+        #vis struct #columns {
+            #(#names: ::std::vec::Vec<#tys>,)*
+        }
+        unsafe impl #krate::Soa for #name {
+            type Columns = #columns;
+            fn push(#cols: &mut #columns, #value: Self) {
+                #(#cols.#names.push(#value.#names);)*
+            }
+            fn swap_remove(#cols: &mut #columns, #index: usize) -> Self {
+                Self {
+                    #(#names: #cols.#names.swap_remove(#index),)*
+                }
+            }
+            fn column(#cols: &#columns, #field: usize) -> *const u8 {
+                match #field {
+                    #(#indices => #cols.#names.as_ptr().cast(),)*
+                    _ => ::core::panic!("no such field"),
+                }
+            }
+            fn column_mut(#cols: &mut #columns, #field: usize) -> *mut u8 {
+                match #field {
+                    #(#indices => #cols.#names.as_mut_ptr().cast(),)*
+                    _ => ::core::panic!("no such field"),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,472 @@
+//! Proc-macros for the `place-projections` crate. Use them through the re-exports there.
+//!
+//! The derives refer to the crate as `::place_projections`. Use
+//! `#[place_projections(crate = path)]` to change that, e.g. when the dependency is renamed.
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Error, Expr, Ident, Path, PathArguments, PathSegment, Result, Token, Type, TypePath, TypePtr,
+    TypeReference, bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    token,
+};
+
+mod derive;
+
+/// The implementation of `p!`, see the docs there. The input starts with `crate = path;`, the
+/// path of the `place-projections` crate, which `p!` passes as `$crate` so that renaming the
+/// dependency works.
+#[proc_macro]
+pub fn p(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as PlaceExpr);
+    Lowering::new(input.krate.clone())
+        .lower(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive a field projection for each field of a struct, named after the field. Like
+/// `mk_field_proj!`, the projections are unit structs, so two structs in the same module can't
+/// both derive projections for fields with the same name. The projections implement
+/// `NamedProjection` with the name of their field, and for sized structs also `FieldIndex` with
+/// its position.
+///
+/// Fields of type `ReadOnly<T>` or `WriteOnly<T>` get a projection to `T` that only implements
+/// `ReadableProjection` or `WritableProjection` respectively, so pointers that care, like
+/// `VolatilePtr`, can forbid the other access.
+///
+/// Fields of `#[repr(packed)]` structs don't get `AlignedProjection`, so they can't be borrowed
+/// as references or read through references; use `UnalignedPtr` instead.
+///
+/// A `#[bits(a..b)] name: Bits<T>` field gets a `BitProjection` to bits `a..b` of the closest
+/// preceding field that isn't a bit field, which must be an unsigned integer. These can only be
+/// read and written through bit-field pointers like `BitMut`: they are constants of a type that
+/// doesn't implement `ByteProjection`, which the other pointers require.
+///
+/// `#[repr(C)]` structs may end in a slice `[T]`, making them unsized with the length of the slice
+/// as metadata. They also get `ByteLen`, and `#[len(field)]` on the slice implements `SliceTail` so
+/// that `BytesRef::with_tail` reads the length from `field`.
+#[proc_macro_derive(FieldProjections, attributes(bits, len, place_projections))]
+pub fn derive_field_projections(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::field_projections(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `EndianConvert` for a struct by swapping each field. The fields must implement
+/// `EndianConvert` and the struct can't have padding.
+#[proc_macro_derive(EndianConvert, attributes(place_projections))]
+pub fn derive_endian_convert(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::endian_convert(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `NoUninit` for a struct whose fields implement it. The struct can't have padding.
+#[proc_macro_derive(NoUninit, attributes(place_projections))]
+pub fn derive_no_uninit(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::no_uninit(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Validate` for a struct whose fields implement it, or for a fieldless enum with an
+/// integer repr like `#[repr(u8)]`.
+#[proc_macro_derive(Validate, attributes(place_projections))]
+pub fn derive_validate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::validate(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Soa` for a struct so that it can be stored in a `SoaVec`, with one column per field in
+/// a generated `{Name}Columns` struct. Use it together with `#[derive(FieldProjections)]` to
+/// project `SoaRef`s to fields.
+#[proc_macro_derive(Soa, attributes(place_projections))]
+pub fn derive_soa(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::soa(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// What we do with the place.
+enum Action {
+    Read,
+    Write(Token![=], Box<Expr>),
+    Borrow(Token![@], Box<Type>),
+}
+
+/// A place expression. Parsed with the same precedence as Rust expressions.
+enum Place {
+    Local(Ident),
+    Deref(Token![*], Box<Place>),
+    Field(Box<Place>, Ident),
+    Index(Box<Place>, token::Bracket, Box<Expr>),
+}
+
+/// The full macro input.
+struct PlaceExpr {
+    krate: Path,
+    safe: bool,
+    action: Action,
+    place: Place,
+}
+
+impl Parse for PlaceExpr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![crate]>()?;
+        input.parse::<Token![=]>()?;
+        let krate = input.call(Path::parse_mod_style)?;
+        input.parse::<Token![;]>()?;
+        // Step 0: check for safe mode. `safe.*` is a place, not the mode.
+        let safe = input.peek(Ident) && !input.peek2(Token![.]) && {
+            let fork = input.fork();
+            fork.parse::<Ident>()? == "safe"
+        };
+        if safe {
+            input.parse::<Ident>()?;
+        }
+        // Step 1: check if we're borrowing or not.
+        let borrow = if input.peek(Token![@]) {
+            let at: Token![@] = input.parse()?;
+            Some((at, Box::new(parse_ptr_ty(input)?)))
+        } else {
+            None
+        };
+        // Step 2: parse the place itself.
+        let place = Place::parse_unary(input)?;
+        // Step 3: detect an assignment, if any.
+        let action = if input.peek(Token![=]) {
+            let eq: Token![=] = input.parse()?;
+            if borrow.is_some() {
+                return Err(Error::new(
+                    eq.span,
+                    "cannot assign to a borrow; remove either the `@Ptr` or the `= value`",
+                ));
+            }
+            Action::Write(eq, input.parse()?)
+        } else if let Some((at, ty)) = borrow {
+            Action::Borrow(at, ty)
+        } else {
+            Action::Read
+        };
+        if !input.is_empty() {
+            return Err(input.error(
+                "unexpected token after place expression; expected `.field`, `.*`, `[index]` \
+                 or `= value`",
+            ));
+        }
+        Ok(PlaceExpr {
+            krate,
+            safe,
+            action,
+            place,
+        })
+    }
+}
+
+/// Parse the target pointer of a borrow: `_`, a reference, a raw pointer or a path with optional
+/// generic arguments. A path without generic arguments gets `<_>` appended, so `@Ptr` means
+/// `@Ptr<_>`.
+fn parse_ptr_ty(input: ParseStream) -> Result<Type> {
+    let mut ty = parse_ty(input)?;
+    if let Type::Path(TypePath { path, .. }) = &mut ty
+        && let Some(last) = path.segments.last_mut()
+        && last.arguments.is_none()
+    {
+        last.arguments = PathArguments::AngleBracketed(parse_quote!(<_>));
+    }
+    Ok(ty)
+}
+
+/// Parse the subset of types we accept as borrow targets. We can't use `Type`'s parser because
+/// it would read `Ptr (*p)` as a `Fn(..)`-style path.
+fn parse_ty(input: ParseStream) -> Result<Type> {
+    if input.peek(Token![_]) {
+        Ok(Type::Infer(input.parse()?))
+    } else if input.peek(Token![&]) {
+        Ok(Type::Reference(TypeReference {
+            and_token: input.parse()?,
+            lifetime: input.parse()?,
+            mutability: input.parse()?,
+            elem: Box::new(parse_ty(input)?),
+        }))
+    } else if input.peek(Token![*]) {
+        let star_token = input.parse()?;
+        let (const_token, mutability) = if input.peek(Token![mut]) {
+            (None, Some(input.parse()?))
+        } else {
+            (Some(input.parse()?), None)
+        };
+        Ok(Type::Ptr(TypePtr {
+            star_token,
+            const_token,
+            mutability,
+            elem: Box::new(parse_ty(input)?),
+        }))
+    } else if input.peek(Ident::peek_any) || input.peek(Token![::]) {
+        let leading_colon = input.parse()?;
+        let mut segments = Punctuated::new();
+        loop {
+            let ident = input.call(Ident::parse_any)?;
+            let arguments = if input.peek(Token![<]) && !input.peek(Token![<=]) {
+                PathArguments::AngleBracketed(input.parse()?)
+            } else {
+                PathArguments::None
+            };
+            segments.push_value(PathSegment { ident, arguments });
+            if !input.peek(Token![::]) {
+                break;
+            }
+            segments.push_punct(input.parse()?);
+        }
+        Ok(Type::Path(TypePath {
+            qself: None,
+            path: Path {
+                leading_colon,
+                segments,
+            },
+        }))
+    } else {
+        Err(input.error(
+            "expected the target pointer after `@`, e.g. `@Ptr`, `@Ptr<T>`, `@&mut _` or `@_`",
+        ))
+    }
+}
+
+impl Place {
+    /// Parse a place with an optional prefix deref.
+    fn parse_unary(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![*]) {
+            let star: Token![*] = input.parse()?;
+            let inner = Self::parse_unary(input)?;
+            Ok(Place::Deref(star, Box::new(inner)))
+        } else {
+            Self::parse_postfix(input)
+        }
+    }
+
+    /// Parse a local or parenthesized place, followed by any number of projections.
+    fn parse_postfix(input: ParseStream) -> Result<Self> {
+        let mut place = if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let place = Self::parse_unary(&content)?;
+            if !content.is_empty() {
+                return Err(content.error("unexpected token in place expression"));
+            }
+            place
+        } else if input.peek(Ident) {
+            Place::Local(input.parse()?)
+        } else {
+            return Err(
+                input.error("expected a place expression, e.g. `(*p).field` or `p.*.field`")
+            );
+        };
+        loop {
+            if input.peek(Token![.]) {
+                input.parse::<Token![.]>()?;
+                if input.peek(Token![*]) {
+                    // A postfix deref.
+                    let star: Token![*] = input.parse()?;
+                    place = Place::Deref(star, Box::new(place));
+                } else if input.peek(Ident) {
+                    place = Place::Field(Box::new(place), input.parse()?);
+                } else {
+                    return Err(input.error("expected a field projection or `*` after `.`"));
+                }
+            } else if input.peek(token::Bracket) {
+                let content;
+                let bracket = bracketed!(content in input);
+                let idx = content.parse()?;
+                if !content.is_empty() {
+                    return Err(content.error("unexpected token in index expression"));
+                }
+                place = Place::Index(Box::new(place), bracket, Box::new(idx));
+            } else {
+                return Ok(place);
+            }
+        }
+    }
+}
+
+/// Builds the final expression. Intermediate pointers are stored in local variables so that we can
+/// take raw pointers to them.
+///
+/// In safe mode we take shared references instead of raw pointers and call the `safe_*` methods,
+/// which require the `SafePlace*` traits.
+struct Lowering {
+    /// The path of the `place-projections` crate.
+    krate: Path,
+    safe: bool,
+    stmts: Vec<TokenStream>,
+    tmp_count: usize,
+    /// The fields we emitted a projection check for, with the name of its function.
+    checked: Vec<(String, Ident)>,
+}
+
+impl Lowering {
+    fn new(krate: Path) -> Self {
+        Self {
+            krate,
+            safe: false,
+            stmts: Vec::new(),
+            tmp_count: 0,
+            checked: Vec::new(),
+        }
+    }
+
+    fn lower(mut self, input: PlaceExpr) -> Result<TokenStream> {
+        self.safe = input.safe;
+        let (ptr, projs) = self.lower_place(input.place)?;
+        let proj = self.compose_projs(&projs);
+        let action = match input.action {
+            Action::Read => {
+                let read = self.method("read", Span::call_site());
+                quote!(#proj.#read(#ptr))
+            }
+            Action::Write(eq, rvalue) => {
+                let write = self.method("write", eq.span);
+                let ptr = self.as_mut_ptr(&ptr);
+                quote!(#proj.#write(#ptr, #rvalue))
+            }
+            Action::Borrow(at, ty) => {
+                let borrow = self.method("borrow", at.span);
+                quote!(#proj.#borrow::<_, #ty>(#ptr))
+            }
+        };
+        let (krate, stmts) = (&self.krate, &self.stmts);
+        Ok(quote!({
+            #[allow(unused_imports)]
+            use #krate::ProjectionExt;
+            #(#stmts)*
+            #action
+        }))
+    }
+
+    /// The `ProjectionExt` method to call for this operation.
+    fn method(&self, name: &str, span: Span) -> Ident {
+        if self.safe {
+            format_ident!("safe_{name}", span = span)
+        } else {
+            Ident::new(name, span)
+        }
+    }
+
+    /// Take a pointer to the local `x`.
+    fn ptr_to(&self, x: &Ident) -> TokenStream {
+        if self.safe {
+            quote!(&#x)
+        } else {
+            quote!(&raw const #x)
+        }
+    }
+
+    /// Turn a pointer we got from `ptr_to` into what `write` and `deref` expect.
+    fn as_mut_ptr(&self, ptr: &Ident) -> TokenStream {
+        if self.safe {
+            quote!(#ptr)
+        } else {
+            quote!(#ptr.cast_mut())
+        }
+    }
+
+    /// Store `value` in a fresh local and return its name.
+    fn bind(&mut self, value: TokenStream) -> Ident {
+        let tmp = format_ident!("ptr{}", self.tmp_count, span = Span::mixed_site());
+        self.tmp_count += 1;
+        self.stmts.push(quote!(let #tmp = #value;));
+        tmp
+    }
+
+    /// Lower `place` to a local holding a `*const X` (or `&X` in safe mode) where `X: HasPlace`, and the list of field
+    /// projections to apply to the place of `X`.
+    fn lower_place(&mut self, place: Place) -> Result<(Ident, Vec<Ident>)> {
+        match place {
+            Place::Local(local) => Err(Error::new(
+                local.span(),
+                format!(
+                    "place expressions must start with a deref, e.g. `(*{local})` or `{local}.*`"
+                ),
+            )),
+            Place::Deref(star, inner) => match *inner {
+                // The pointer is a local: start from there.
+                Place::Local(local) => {
+                    let ptr = self.ptr_to(&local);
+                    Ok((self.bind(ptr), vec![]))
+                }
+                // Deref a complex place expression.
+                inner => {
+                    let (ptr, projs) = self.lower_place(inner)?;
+                    let proj = self.compose_projs(&projs);
+                    let deref = self.method("deref", star.span);
+                    let ptr = self.as_mut_ptr(&ptr);
+                    let ptr = self.bind(quote!(#proj.#deref(#ptr)));
+                    Ok((ptr, vec![]))
+                }
+            },
+            Place::Field(inner, field) => {
+                let (ptr, mut projs) = self.lower_place(*inner)?;
+                projs.push(field);
+                Ok((ptr, projs))
+            }
+            Place::Index(inner, bracket, idx) => {
+                let (ptr, projs) = self.lower_place(*inner)?;
+                let proj = self.compose_projs(&projs);
+                let index = self.method("index", bracket.span.join());
+                let elem = self.bind(quote!(#proj.#index(#ptr, #idx)));
+                let ptr = self.ptr_to(&elem);
+                Ok((self.bind(ptr), vec![]))
+            }
+        }
+    }
+
+    /// Compose some field projections.
+    fn compose_projs(&mut self, projs: &[Ident]) -> TokenStream {
+        let krate = self.krate.clone();
+        projs
+            .iter()
+            .rev()
+            .fold(quote!(#krate::NoopProj::default()), |acc, field| {
+                let check = self.field_check(field);
+                quote_spanned!(field.span()=> #check(#field).compose(#acc))
+            })
+    }
+
+    /// A function that returns its argument if it's a projection, and otherwise fails with an
+    /// error that names the field. Without it, using a value that isn't a projection as a field
+    /// only gives "no method named `compose`".
+    fn field_check(&mut self, field: &Ident) -> Ident {
+        let name = field.unraw().to_string();
+        if let Some((_, check)) = self.checked.iter().find(|(n, _)| *n == name) {
+            return check.clone();
+        }
+        let check = format_ident!("check_{}", self.checked.len(), span = Span::mixed_site());
+        let message = format!("field `{name}` has no projection in scope");
+        let note = format!(
+            "declare one with `mk_field_proj!(struct {name}(Struct.{name}: Type))` or \
+             `#[derive(FieldProjections)]` on the struct"
+        );
+        let krate = &self.krate;
+        let tr = format_ident!("Check{}", self.checked.len(), span = Span::mixed_site());
+        self.stmts.push(quote! {
+            #[diagnostic::on_unimplemented(message = #message, label = "not a projection", note = #note)]
+            trait #tr {}
+            impl<P: #krate::Projection> #tr for P {}
+            const fn #check<P: #tr>(p: P) -> P {
+                p
+            }
+        });
+        self.checked.push((name, check.clone()));
+        check
+    }
+}
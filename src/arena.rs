@@ -0,0 +1,407 @@
+use std::{
+    any::TypeId,
+    cell::{RefCell, UnsafeCell},
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::Pointee,
+};
+
+use crate::*;
+
+/// A handle to a value in an `Arena<T>`: an index plus the generation of the slot, so that handles
+/// to removed values are detected even if the slot was reused.
+pub struct Idx<T> {
+    index: u32,
+    generation: u32,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Idx<T> {
+    /// A handle that never points to a value, as a placeholder.
+    pub const fn dangling() -> Self {
+        Self {
+            index: u32::MAX,
+            generation: 0,
+            phantom: PhantomData,
+        }
+    }
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Idx<T> {}
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+impl<T> Eq for Idx<T> {}
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.index, self.generation).hash(state);
+    }
+}
+impl<T> fmt::Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Idx({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<UnsafeCell<T>>,
+}
+
+/// A `Vec` of values addressed by `Idx<T>` handles. Removed slots are reused with a new
+/// generation.
+///
+/// Values are accessed in place through `ArenaRef`s, which may write to them from a shared
+/// reference to the arena, like a `Cell`.
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn insert(&mut self, x: T) -> Idx<T> {
+        let value = Some(UnsafeCell::new(x));
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = value;
+                index
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("too many arena slots");
+                self.slots.push(Slot {
+                    generation: 0,
+                    value,
+                });
+                index
+            }
+        };
+        Idx {
+            index,
+            generation: self.slots[index as usize].generation,
+            phantom: PhantomData,
+        }
+    }
+    /// Remove the value of `idx`, making all the handles to it stale.
+    pub fn remove(&mut self, idx: Idx<T>) -> Option<T> {
+        self.cell(idx)?;
+        let slot = &mut self.slots[idx.index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(idx.index);
+        slot.value.take().map(UnsafeCell::into_inner)
+    }
+    /// Whether `idx` points to a value, i.e. isn't stale.
+    pub fn contains(&self, idx: Idx<T>) -> bool {
+        self.cell(idx).is_some()
+    }
+    pub fn get_mut(&mut self, idx: Idx<T>) -> Option<&mut T> {
+        self.cell(idx).map(|cell| unsafe { &mut *cell.get() })
+    }
+    fn cell(&self, idx: Idx<T>) -> Option<&UnsafeCell<T>> {
+        let slot = self.slots.get(idx.index as usize)?;
+        (slot.generation == idx.generation)
+            .then_some(slot.value.as_ref())
+            .flatten()
+    }
+
+    /// Pointer to the value of `idx`, or `None` if the handle is stale.
+    pub fn try_at(&self, idx: Idx<T>) -> Option<ArenaRef<'_, T>> {
+        self.cell(idx)?;
+        Some(ArenaRef {
+            arena: self,
+            idx,
+            proj: ErasedProj::identity(()),
+        })
+    }
+    /// Pointer to the value of `idx`. Panics if the handle is stale.
+    pub fn at(&self, idx: Idx<T>) -> ArenaRef<'_, T> {
+        self.try_at(idx)
+            .unwrap_or_else(|| panic!("stale arena index {idx:?}"))
+    }
+}
+
+thread_local! {
+    /// The arenas `Idx<T>` handles point into, by `TypeId` of `T`.
+    static ENTERED: RefCell<HashMap<TypeId, *const ()>> = RefCell::default();
+}
+
+impl<T: 'static> Arena<T> {
+    /// Make this the arena that `Idx<T>` handles point into on this thread while `f` runs. This
+    /// is what place operations on `Idx<T>` fields use, e.g. `p!((*(*entity).parent).name)`
+    /// where `parent: Idx<Entity>`. Calls nest: the previously entered arena is restored when
+    /// `f` returns or unwinds.
+    pub fn with_entered<R>(&self, f: impl FnOnce() -> R) -> R {
+        let prev = ENTERED.with_borrow_mut(|entered| {
+            entered.insert(TypeId::of::<T>(), std::ptr::from_ref(self).cast())
+        });
+        let _scope = ArenaScope { arena: self, prev };
+        f()
+    }
+
+    /// The arena entered for `T`.
+    ///
+    /// # Safety
+    ///
+    /// The arena must stay entered for as long as the result is used.
+    unsafe fn entered<'a>() -> &'a Self {
+        let arena = ENTERED.with_borrow(|entered| entered.get(&TypeId::of::<T>()).copied());
+        let arena = arena.unwrap_or_else(|| {
+            panic!(
+                "no `Arena<{}>` entered; call `Arena::with_entered` first",
+                std::any::type_name::<T>()
+            )
+        });
+        unsafe { &*arena.cast() }
+    }
+}
+
+/// Restores the previously entered arena when `Arena::with_entered` is done. It is private and
+/// lives on the stack of `with_entered`, so it can't be forgotten or dropped out of order.
+struct ArenaScope<'a, T: 'static> {
+    arena: &'a Arena<T>,
+    prev: Option<*const ()>,
+}
+impl<T: 'static> Drop for ArenaScope<'_, T> {
+    fn drop(&mut self) {
+        ENTERED.with_borrow_mut(|entered| {
+            let current = entered.get(&TypeId::of::<T>()).copied();
+            assert_eq!(
+                current,
+                Some(std::ptr::from_ref(self.arena).cast()),
+                "`Arena` scopes exited out of order"
+            );
+            match self.prev {
+                Some(prev) => entered.insert(TypeId::of::<T>(), prev),
+                None => entered.remove(&TypeId::of::<T>()),
+            }
+        });
+    }
+}
+
+/// A pointer to a part `T` of a value in an `Arena<W>`: the arena, the handle and a projection
+/// from the whole value. The handle is checked when the pointer is made, and the arena can't
+/// remove values while it is borrowed, so it stays valid.
+///
+/// Fields of type `Idx<U>` can be dereferenced inside `Arena::with_entered` for an `Arena<U>`:
+/// the handle is checked against it and further place operations happen there.
+pub struct ArenaRef<'a, T: ?Sized, W = T> {
+    arena: &'a Arena<W>,
+    idx: Idx<W>,
+    proj: ErasedProj<W, T>,
+}
+
+impl<'a, T: ?Sized, W> ArenaRef<'a, T, W> {
+    pub fn idx(&self) -> Idx<W> {
+        self.idx
+    }
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.proj.metadata()
+    }
+    fn project<P>(&self, p: &P) -> ArenaRef<'a, P::Target, W>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        ArenaRef {
+            arena: self.arena,
+            idx: self.idx,
+            proj: self.proj.then(p),
+        }
+    }
+    fn as_ptr(&self) -> *mut T {
+        let cell = &self.arena.slots[self.idx.index as usize].value;
+        let whole = cell.as_ref().unwrap().get();
+        let ptr = unsafe { whole.byte_add(self.proj.byte_offset()) };
+        std::ptr::from_raw_parts_mut(ptr, self.proj.metadata())
+    }
+}
+
+impl<T: ?Sized, W> Clone for ArenaRef<'_, T, W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized, W> Copy for ArenaRef<'_, T, W> {}
+
+impl<T: ?Sized, W> HasPlace for ArenaRef<'_, T, W> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P, W> PlaceBorrow<'a, P, ArenaRef<'a, P::Target, W>>
+    for ArenaRef<'b, P::Source, W>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> ArenaRef<'a, P::Target, W> {
+        unsafe { (*ptr).project(p) }
+    }
+}
+
+unsafe impl<P, W> PlaceRead<P> for ArenaRef<'_, P::Source, W>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).as_ptr().read() }
+    }
+}
+
+unsafe impl<P, W> PlaceWrite<P> for ArenaRef<'_, P::Source, W>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).as_ptr().write(x) }
+    }
+}
+
+unsafe impl<P, U, W> PlaceDeref<P> for ArenaRef<'_, P::Source, W>
+where
+    P: Projection<Target = Idx<U>> + ByteProjection + ?Sized,
+    U: 'static,
+{
+    unsafe fn double_deref(ptr: *mut Self, p: &P) -> *const Idx<U> {
+        unsafe {
+            let place = (*ptr).project(p).as_ptr();
+            // Check the handle now, so that stale ones are caught where they are followed.
+            Arena::<U>::entered().at(*place);
+            place
+        }
+    }
+}
+
+unsafe impl<'a, P, I, W> PlaceIndex<P, I> for ArenaRef<'a, P::Source, W>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = ArenaRef<'a, <P::Target as IndexProjection<I>>::Output, W>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        unsafe { (*ptr).project(p).project(&q) }
+    }
+}
+
+// Arena values are always aligned, and no references to them can exist while the arena is
+// shared, so `Copy` values can be read and written like in a `Cell`.
+unsafe impl<'a, 'b: 'a, P, W> SafePlaceBorrow<'a, P, ArenaRef<'a, P::Target, W>>
+    for ArenaRef<'b, P::Source, W>
+where
+    P: SafeProjection + ?Sized,
+{
+}
+unsafe impl<P, W> SafePlaceRead<P> for ArenaRef<'_, P::Source, W>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P, W> SafePlaceWrite<P> for ArenaRef<'_, P::Source, W>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P, I, W> SafePlaceIndex<P, I> for ArenaRef<'_, P::Source, W>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
+
+// A dereferenced `Idx<T>` field: place operations go to the entered `Arena<T>`. These are only
+// sound while that arena stays entered, so there are no safe versions: callers of the unsafe
+// place operations must run them inside `Arena::with_entered`, and not use the `ArenaRef`s they
+// borrow after it returns.
+impl<T> HasPlace for Idx<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, ArenaRef<'a, P::Target, P::Source>> for Idx<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized + 'static,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> ArenaRef<'a, P::Target, P::Source> {
+        unsafe { Arena::entered().at(*ptr).project(p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for Idx<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized + 'static,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        let place = unsafe { Arena::entered().at(*ptr) };
+        unsafe { p.read(&raw const place) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for Idx<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized + 'static,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        let mut place = unsafe { Arena::entered().at(*ptr) };
+        unsafe { p.write(&raw mut place, x) }
+    }
+}
+
+unsafe impl<P, U> PlaceDeref<P> for Idx<P::Source>
+where
+    P: Projection<Target = Idx<U>> + ByteProjection + ?Sized,
+    P::Source: Sized + 'static,
+    U: 'static,
+{
+    unsafe fn double_deref(ptr: *mut Self, p: &P) -> *const Idx<U> {
+        let mut place = unsafe { Arena::entered().at(*ptr) };
+        unsafe { p.deref(&raw mut place) }
+    }
+}
@@ -28,7 +28,7 @@ impl_has_place_with_lt!(SharedRef);
 impl_has_place_with_lt!(MutRef);
 
 // The two basic impls everything else is derived from.
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     for RawConst<P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -41,7 +41,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
         }
     }
 }
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawMut<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawMut<P::Target>>
     for RawMut<P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -55,7 +55,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawMut<P::Target>>
     }
 }
 
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, NonNull<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, NonNull<P::Target>>
     for NonNull<P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -66,7 +66,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, NonNull<P::Target>>
         }
     }
 }
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     for NonNull<P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -74,7 +74,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
         unsafe { p.borrow::<NonNull<_>, NonNull<_>>(ptr).as_ptr() }
     }
 }
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     for RawMut<P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -82,7 +82,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
         unsafe { p.borrow::<RawMut<_>, RawMut<_>>(ptr) }
     }
 }
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     for SharedRef<'_, P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -93,7 +93,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
         }
     }
 }
-unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
+unsafe impl<'a, P: Projection + ByteProjection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     for MutRef<'_, P::Source>
 {
     const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
@@ -105,7 +105,7 @@ unsafe impl<'a, P: Projection + ?Sized> PlaceBorrow<'a, P, RawConst<P::Target>>
     }
 }
 
-unsafe impl<P: Projection + ?Sized> PlaceDeref<P> for NonNull<P::Source>
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceDeref<P> for NonNull<P::Source>
 where
     P::Target: HasPlace,
 {
@@ -113,7 +113,7 @@ where
         unsafe { p.borrow(ptr) }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceDeref<P> for RawConst<P::Source>
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceDeref<P> for RawConst<P::Source>
 where
     P::Target: HasPlace,
 {
@@ -121,7 +121,7 @@ where
         unsafe { p.borrow(ptr) }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceDeref<P> for RawMut<P::Source>
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceDeref<P> for RawMut<P::Source>
 where
     P::Target: HasPlace,
 {
@@ -129,7 +129,7 @@ where
         unsafe { p.borrow(ptr) }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceDeref<P> for SharedRef<'_, P::Source>
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceDeref<P> for SharedRef<'_, P::Source>
 where
     P::Target: HasPlace,
 {
@@ -137,7 +137,7 @@ where
         unsafe { p.borrow(ptr) }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceDeref<P> for MutRef<'_, P::Source>
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceDeref<P> for MutRef<'_, P::Source>
 where
     P::Target: HasPlace,
 {
@@ -146,15 +146,93 @@ where
     }
 }
 
-unsafe impl<P: Projection + ?Sized> PlaceRead<P> for RawConst<P::Source> {
+unsafe impl<P, I> PlaceIndex<P, I> for RawConst<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = RawConst<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let place = p.borrow::<RawConst<_>, RawConst<_>>(ptr);
+            let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+            q.borrow::<RawConst<_>, RawConst<_>>(&raw const place)
+        }
+    }
+}
+unsafe impl<P, I> PlaceIndex<P, I> for RawMut<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = RawMut<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let place = p.borrow::<RawMut<_>, RawMut<_>>(ptr);
+            let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+            q.borrow::<RawMut<_>, RawMut<_>>(&raw const place)
+        }
+    }
+}
+unsafe impl<P, I> PlaceIndex<P, I> for NonNull<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = NonNull<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let ptr: *mut _ = (*ptr).as_ptr();
+            NonNull::new_unchecked(p.index(&raw const ptr, idx))
+        }
+    }
+}
+unsafe impl<'a, P, I> PlaceIndex<P, I> for SharedRef<'a, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    // The indexed place may only be misaligned if the subplace is.
+    P: AlignedProjection,
+    <P::Target as IndexProjection<I>>::Output: 'a,
+{
+    type Output = SharedRef<'a, <P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let r: *const *const Self::Target = ptr.cast();
+            &*p.index(r, idx)
+        }
+    }
+}
+unsafe impl<P, I> PlaceIndex<P, I> for MutRef<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = RawMut<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let r: *const *mut Self::Target = ptr.cast();
+            p.index(r, idx)
+        }
+    }
+}
+
+// Raw pointers do aligned accesses, which is UB for most fields of `#[repr(packed)]` structs.
+const MISALIGNED: &str = "misaligned access; use `UnalignedPtr` for fields of packed structs";
+
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceRead<P> for RawConst<P::Source> {
     unsafe fn read(ptr: *const Self, p: &P) -> P::Target
     where
         P::Target: Sized,
     {
-        unsafe { p.borrow::<RawConst<_>, RawConst<_>>(ptr).read() }
+        unsafe {
+            let place = p.borrow::<RawConst<_>, RawConst<_>>(ptr);
+            debug_assert!(place.is_aligned(), "{MISALIGNED}");
+            place.read()
+        }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceRead<P> for RawMut<P::Source> {
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceRead<P> for RawMut<P::Source> {
     unsafe fn read(ptr: *const Self, p: &P) -> P::Target
     where
         P::Target: Sized,
@@ -163,7 +241,7 @@ unsafe impl<P: Projection + ?Sized> PlaceRead<P> for RawMut<P::Source> {
         unsafe { p.read(ptr.cast::<*const _>()) }
     }
 }
-unsafe impl<P: Projection + ?Sized> PlaceRead<P> for SharedRef<'_, P::Source> {
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceRead<P> for SharedRef<'_, P::Source> {
     unsafe fn read(ptr: *const Self, p: &P) -> P::Target
     where
         P::Target: Sized,
@@ -173,19 +251,23 @@ unsafe impl<P: Projection + ?Sized> PlaceRead<P> for SharedRef<'_, P::Source> {
     }
 }
 
-unsafe impl<P: Projection + ?Sized> PlaceWrite<P> for RawMut<P::Source> {
+unsafe impl<P: Projection + ByteProjection + ?Sized> PlaceWrite<P> for RawMut<P::Source> {
     unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
     where
         P::Target: Sized,
     {
-        unsafe { p.borrow::<RawMut<_>, RawMut<_>>(ptr).write(x) }
+        unsafe {
+            let place = p.borrow::<RawMut<_>, RawMut<_>>(ptr);
+            debug_assert!(place.is_aligned(), "{MISALIGNED}");
+            place.write(x)
+        }
     }
 }
 
-unsafe impl<'a, 'b, T: ?Sized> PlaceCoerce<&'a &'b T> for &'b T {
+unsafe impl<'b, T: ?Sized> PlaceCoerce<&&'b T> for &'b T {
     type Output = &'b T;
 }
-unsafe impl<'a, 'b, T: ?Sized> PlaceCoerce<&'a mut &'b T> for &'b T {
+unsafe impl<'b, T: ?Sized> PlaceCoerce<&mut &'b T> for &'b T {
     type Output = &'b T;
 }
 unsafe impl<'a, 'b, T: ?Sized> PlaceCoerce<&'a &'b mut T> for &'b mut T {
@@ -194,3 +276,47 @@ unsafe impl<'a, 'b, T: ?Sized> PlaceCoerce<&'a &'b mut T> for &'b mut T {
 unsafe impl<'a, 'b, T: ?Sized> PlaceCoerce<&'a mut &'b mut T> for &'b mut T {
     type Output = &'a mut T;
 }
+
+// Shared and mutable references point to valid places, so anything that only creates pointers
+// inside them or reads `Copy` data out of them is safe.
+unsafe impl<'a, P: SafeProjection + ?Sized> SafePlaceBorrow<'a, P, RawConst<P::Target>>
+    for SharedRef<'_, P::Source>
+{
+}
+unsafe impl<'a, P: SafeProjection + ?Sized> SafePlaceBorrow<'a, P, RawConst<P::Target>>
+    for MutRef<'_, P::Source>
+{
+}
+unsafe impl<P> SafePlaceDeref<P> for SharedRef<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: HasPlace,
+{
+}
+unsafe impl<P> SafePlaceDeref<P> for MutRef<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: HasPlace,
+{
+}
+unsafe impl<'a, P, I> SafePlaceIndex<P, I> for SharedRef<'a, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection + AlignedProjection,
+    <P::Target as IndexProjection<I>>::Output: 'a,
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for MutRef<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection + AlignedProjection,
+{
+}
+unsafe impl<P> SafePlaceRead<P> for SharedRef<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
@@ -0,0 +1,242 @@
+use std::{marker::PhantomData, ptr::NonNull};
+
+use crate::*;
+
+/// Values that can be stored in a bit field: `bool` and unsigned integers.
+pub trait BitValue: Copy {
+    /// The number of bits needed to store any value.
+    const BITS: u32;
+    fn to_bits(self) -> u128;
+    /// Build a value from its low `Self::BITS` bits; higher bits are zero.
+    fn from_bits(bits: u128) -> Self;
+}
+
+/// Integers that bit fields can be packed into.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value.
+pub unsafe trait BitStorage: BitValue {}
+
+impl BitValue for bool {
+    const BITS: u32 = 1;
+    fn to_bits(self) -> u128 {
+        self as u128
+    }
+    fn from_bits(bits: u128) -> Self {
+        bits != 0
+    }
+}
+
+macro_rules! impl_bit_value_int {
+    ($($ty:ty),*) => {
+        $(impl BitValue for $ty {
+            const BITS: u32 = <$ty>::BITS;
+            fn to_bits(self) -> u128 {
+                self as u128
+            }
+            fn from_bits(bits: u128) -> Self {
+                bits as $ty
+            }
+        }
+        unsafe impl BitStorage for $ty {})*
+    };
+}
+impl_bit_value_int!(u8, u16, u32, u64, u128);
+
+/// Marker field for a bit field of type `T`. With `#[derive(FieldProjections)]`, a
+/// `#[bits(a..b)] name: Bits<T>` field gets a `BitProjection` to bits `a..b` of the closest
+/// preceding field that isn't a bit field.
+///
+/// ```text
+/// #[derive(FieldProjections)]
+/// #[repr(C)]
+/// struct Flags {
+///     raw: u32,
+///     #[bits(0..1)]
+///     ack: Bits<bool>,
+///     #[bits(3..7)]
+///     window: Bits<u8>,
+/// }
+/// ```
+pub struct Bits<T>(PhantomData<T>);
+impl<T> Bits<T> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+impl<T> Default for Bits<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> Clone for Bits<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Bits<T> {}
+impl<T> PartialEq for Bits<T> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+impl<T> Eq for Bits<T> {}
+impl<T> std::fmt::Debug for Bits<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Bits")
+    }
+}
+
+/// Read the bit field `p` of the place of `ptr`.
+unsafe fn read_bits<P>(ptr: *const P::Source, p: &P) -> P::Target
+where
+    P: BitProjection + ?Sized,
+{
+    let range = p.bit_range();
+    let storage = unsafe { storage(ptr.cast_mut(), p) };
+    let word = unsafe { storage.read_unaligned() }.to_bits();
+    P::Target::from_bits((word >> range.start) & mask(&range))
+}
+
+/// Write the bit field `p` of the place of `ptr`, leaving the other bits unchanged. Panics if `x`
+/// doesn't fit.
+unsafe fn write_bits<P>(ptr: *mut P::Source, p: &P, x: P::Target)
+where
+    P: BitProjection + ?Sized,
+{
+    let range = p.bit_range();
+    let mask = mask(&range);
+    let bits = x.to_bits();
+    assert!(bits & !mask == 0, "value doesn't fit in the bit field");
+    let storage = unsafe { storage(ptr, p) };
+    let word = unsafe { storage.read_unaligned() }.to_bits();
+    let word = (word & !(mask << range.start)) | (bits << range.start);
+    unsafe { storage.write_unaligned(P::Storage::from_bits(word)) }
+}
+
+/// The integer that holds the bit field `p`.
+unsafe fn storage<P>(ptr: *mut P::Source, p: &P) -> *mut P::Storage
+where
+    P: BitProjection + ?Sized,
+{
+    let (addr, meta) = ptr.to_raw_parts();
+    unsafe { addr.byte_add(p.offset(meta)).cast() }
+}
+
+fn mask(range: &std::ops::Range<u32>) -> u128 {
+    u128::MAX >> (128 - (range.end - range.start))
+}
+
+/// A shared reference that can read bit fields. Other subplaces can be borrowed as `BitRef`s but
+/// not read, since the same `PlaceRead` impl can't also handle byte projections.
+pub struct BitRef<'a, T: ?Sized>(NonNull<T>, PhantomData<&'a T>);
+
+impl<'a, T: ?Sized> BitRef<'a, T> {
+    pub fn new(r: &'a T) -> Self {
+        Self(NonNull::from(r), PhantomData)
+    }
+    pub fn get(&self) -> &'a T {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T: ?Sized> Clone for BitRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for BitRef<'_, T> {}
+
+impl<T: ?Sized> HasPlace for BitRef<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, BitRef<'a, P::Target>> for BitRef<'b, P::Source>
+where
+    P: AlignedProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> BitRef<'a, P::Target> {
+        unsafe {
+            BitRef(
+                p.borrow::<NonNull<_>, NonNull<_>>(&raw const (*ptr).0),
+                PhantomData,
+            )
+        }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for BitRef<'_, P::Source>
+where
+    P: BitProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target {
+        unsafe { read_bits((*ptr).0.as_ptr(), p) }
+    }
+}
+
+/// A mutable reference that can read and write bit fields, e.g. `p!((*hdr).flags.ack = true)`.
+/// Writes only change the bits of the field. Like `BitRef`, other subplaces can only be
+/// borrowed.
+pub struct BitMut<'a, T: ?Sized>(NonNull<T>, PhantomData<&'a mut T>);
+
+impl<'a, T: ?Sized> BitMut<'a, T> {
+    pub fn new(r: &'a mut T) -> Self {
+        Self(NonNull::from(r), PhantomData)
+    }
+    pub fn into_mut(mut self) -> &'a mut T {
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl<T: ?Sized> HasPlace for BitMut<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, BitMut<'a, P::Target>> for BitMut<'b, P::Source>
+where
+    P: AlignedProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Unique;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> BitMut<'a, P::Target> {
+        unsafe {
+            BitMut(
+                p.borrow::<NonNull<_>, NonNull<_>>(&raw const (*ptr).0),
+                PhantomData,
+            )
+        }
+    }
+}
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, BitRef<'a, P::Target>> for BitMut<'b, P::Source>
+where
+    P: AlignedProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> BitRef<'a, P::Target> {
+        unsafe {
+            BitRef(
+                p.borrow::<NonNull<_>, NonNull<_>>(&raw const (*ptr).0),
+                PhantomData,
+            )
+        }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for BitMut<'_, P::Source>
+where
+    P: BitProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target {
+        unsafe { read_bits((*ptr).0.as_ptr(), p) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for BitMut<'_, P::Source>
+where
+    P: BitProjection + ?Sized,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        unsafe { write_bits((*ptr).0.as_ptr(), p, x) }
+    }
+}
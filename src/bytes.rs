@@ -0,0 +1,374 @@
+use std::{fmt, marker::PhantomData, ptr::Pointee};
+
+use crate::*;
+
+/// Types whose bit patterns can be checked, so that they can be read from untrusted bytes.
+/// Implemented for integers, floats, `bool`, `char`, arrays of those, and with
+/// `#[derive(Validate)]` for structs and fieldless enums.
+///
+/// # Safety
+///
+/// `validate` must only return `true` if `bytes` is a valid `Self`.
+pub unsafe trait Validate: Sized {
+    /// Check the bytes of a value, which are `size_of::<Self>()` long.
+    fn validate(bytes: &[u8]) -> bool;
+}
+
+macro_rules! impl_validate_any {
+    ($($ty:ty),*) => {
+        $(unsafe impl Validate for $ty {
+            fn validate(bytes: &[u8]) -> bool {
+                bytes.len() == size_of::<$ty>()
+            }
+        })*
+    };
+}
+impl_validate_any!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    ()
+);
+
+unsafe impl Validate for bool {
+    fn validate(bytes: &[u8]) -> bool {
+        matches!(bytes, [0 | 1])
+    }
+}
+unsafe impl Validate for char {
+    fn validate(bytes: &[u8]) -> bool {
+        bytes
+            .try_into()
+            .is_ok_and(|b| char::from_u32(u32::from_ne_bytes(b)).is_some())
+    }
+}
+unsafe impl<T: Validate, const N: usize> Validate for [T; N] {
+    fn validate(bytes: &[u8]) -> bool {
+        bytes.len() == size_of::<Self>()
+            && (size_of::<T>() == 0 || bytes.chunks_exact(size_of::<T>()).all(T::validate))
+    }
+}
+
+/// Types whose size can be computed from their metadata, checking for overflow. Structs ending in
+/// a slice get an impl from `#[derive(FieldProjections)]`.
+///
+/// # Safety
+///
+/// `byte_len` must return `size_of_val` of a value with that metadata, if it fits in `isize`.
+pub unsafe trait ByteLen {
+    /// Number of bytes of a value with the given metadata, or `None` if it doesn't fit in
+    /// memory.
+    fn byte_len(meta: <Self as Pointee>::Metadata) -> Option<usize>;
+}
+unsafe impl<T> ByteLen for T {
+    fn byte_len(_: ()) -> Option<usize> {
+        Some(size_of::<T>())
+    }
+}
+unsafe impl<T> ByteLen for [T] {
+    fn byte_len(len: usize) -> Option<usize> {
+        len.checked_mul(size_of::<T>())
+            .filter(|&n| n <= isize::MAX as usize)
+    }
+}
+
+/// Size of a `#[repr(C)]` struct `T` whose last field is a `[E]` of length `len` at
+/// `tail_offset`. Used by `#[derive(FieldProjections)]`.
+#[doc(hidden)]
+pub fn slice_tail_byte_len<T, E>(tail_offset: usize, len: usize) -> Option<usize>
+where
+    T: Pointee<Metadata = usize> + ?Sized,
+{
+    // The alignment doesn't depend on the length, and a length of 0 can't overflow.
+    let align = unsafe {
+        std::mem::align_of_val_raw(std::ptr::from_raw_parts::<T>(std::ptr::null::<()>(), 0))
+    };
+    len.checked_mul(size_of::<E>())?
+        .checked_add(tail_offset)?
+        .checked_next_multiple_of(align)
+        .filter(|&n| n <= isize::MAX as usize)
+}
+
+/// Structs ending in a slice whose length is stored in one of the fields before it, declared with
+/// `#[len(field)]` on the slice with `#[derive(FieldProjections)]`. `BytesRef::with_tail` reads
+/// the length from there.
+pub trait SliceTail: ByteLen + Pointee<Metadata = usize> {
+    type Len: Validate + TryInto<usize>;
+    type LenField: Projection<Source = Self, Target = Self::Len>;
+    const LEN_FIELD: Self::LenField;
+}
+
+/// Why bytes can't be accessed as a place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytesError {
+    /// The place at `offset` with size `len` doesn't fit in the buffer (`None` if the size
+    /// overflows).
+    OutOfBounds {
+        offset: usize,
+        len: Option<usize>,
+        buf_len: usize,
+    },
+    /// The bytes at `offset` are not a valid `ty`.
+    Invalid { offset: usize, ty: &'static str },
+    /// The place at `offset` is not aligned for a reference to `ty`.
+    Misaligned { offset: usize, ty: &'static str },
+}
+impl fmt::Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds {
+                offset,
+                len: Some(len),
+                buf_len,
+            } => write!(
+                f,
+                "{len} bytes at offset {offset} are out of a buffer of length {buf_len}"
+            ),
+            Self::OutOfBounds { offset, .. } => {
+                write!(f, "the place at offset {offset} is too large")
+            }
+            Self::Invalid { offset, ty } => {
+                write!(f, "the bytes at offset {offset} are not a valid `{ty}`")
+            }
+            Self::Misaligned { offset, ty } => {
+                write!(f, "the `{ty}` at offset {offset} is misaligned")
+            }
+        }
+    }
+}
+impl std::error::Error for BytesError {}
+
+/// A `T` inside a buffer of untrusted bytes, e.g. a binary blob being parsed. Nothing is copied
+/// until a place is read, and then only the bytes of that place.
+///
+/// Borrowing a subplace checks that it fits in the buffer, and reading checks its bytes with
+/// `Validate`. Place operations can't return errors, so they panic on bad input; the `try_*`
+/// methods return a `BytesError` instead. Reads are unaligned; borrowing a reference also checks
+/// the alignment.
+pub struct BytesRef<'a, T: ?Sized> {
+    buf: &'a [u8],
+    offset: usize,
+    meta: <T as Pointee>::Metadata,
+    phantom: PhantomData<*const T>,
+}
+
+impl<'a, T> BytesRef<'a, T> {
+    /// The `T` at the start of `buf`.
+    pub fn new(buf: &'a [u8]) -> Result<Self, BytesError> {
+        Self::from_raw_parts(buf, ())
+    }
+}
+impl<'a, T: SliceTail + ?Sized> BytesRef<'a, T> {
+    /// The `T` at the start of `buf`, with the length of its slice tail read from its header.
+    pub fn with_tail(buf: &'a [u8]) -> Result<Self, BytesError> {
+        let offset = T::LEN_FIELD.offset(0);
+        let len_field = BytesRef::<T::Len>::at(buf, offset, ())?;
+        let len = len_field
+            .try_read()?
+            .try_into()
+            .map_err(|_| BytesError::OutOfBounds {
+                offset,
+                len: None,
+                buf_len: buf.len(),
+            })?;
+        Self::from_raw_parts(buf, len)
+    }
+}
+impl<'a, T: ByteLen + ?Sized> BytesRef<'a, T> {
+    /// The `T` with metadata `meta` at the start of `buf`.
+    pub fn from_raw_parts(
+        buf: &'a [u8],
+        meta: <T as Pointee>::Metadata,
+    ) -> Result<Self, BytesError> {
+        Self::at(buf, 0, meta)
+    }
+    fn at(
+        buf: &'a [u8],
+        offset: usize,
+        meta: <T as Pointee>::Metadata,
+    ) -> Result<Self, BytesError> {
+        let len = T::byte_len(meta);
+        if len
+            .and_then(|len| offset.checked_add(len))
+            .is_none_or(|end| end > buf.len())
+        {
+            return Err(BytesError::OutOfBounds {
+                offset,
+                len,
+                buf_len: buf.len(),
+            });
+        }
+        Ok(Self {
+            buf,
+            offset,
+            meta,
+            phantom: PhantomData,
+        })
+    }
+
+    /// The subplace `p`, if it fits in the buffer.
+    pub fn try_project<P>(&self, p: &P) -> Result<BytesRef<'a, P::Target>, BytesError>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+        P::Target: ByteLen,
+    {
+        BytesRef::at(
+            self.buf,
+            self.offset + p.offset(self.meta),
+            p.project_metadata(self.meta),
+        )
+    }
+}
+impl<'a, T: ?Sized> BytesRef<'a, T> {
+    /// Offset of the place in the buffer.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.meta
+    }
+}
+impl<'a, T: Validate> BytesRef<'a, T> {
+    fn bytes(&self) -> &'a [u8] {
+        &self.buf[self.offset..][..size_of::<T>()]
+    }
+    /// Copy the `T` out of the buffer, if its bytes are valid.
+    pub fn try_read(&self) -> Result<T, BytesError> {
+        let bytes = self.bytes();
+        if !T::validate(bytes) {
+            return Err(BytesError::Invalid {
+                offset: self.offset,
+                ty: std::any::type_name::<T>(),
+            });
+        }
+        Ok(unsafe { bytes.as_ptr().cast::<T>().read_unaligned() })
+    }
+    /// Borrow the `T` in place, if its bytes are valid and it is aligned.
+    pub fn try_get(&self) -> Result<&'a T, BytesError> {
+        let ptr = self.bytes().as_ptr().cast::<T>();
+        if !ptr.is_aligned() {
+            return Err(BytesError::Misaligned {
+                offset: self.offset,
+                ty: std::any::type_name::<T>(),
+            });
+        }
+        self.try_read()?;
+        Ok(unsafe { &*ptr })
+    }
+}
+impl<'a> BytesRef<'a, [u8]> {
+    /// The bytes themselves.
+    pub fn as_slice(&self) -> &'a [u8] {
+        &self.buf[self.offset..][..self.meta]
+    }
+}
+
+impl<T: ?Sized> Clone for BytesRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for BytesRef<'_, T> {}
+
+impl<T: ?Sized> HasPlace for BytesRef<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, BytesRef<'a, P::Target>> for BytesRef<'b, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: ByteLen,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> BytesRef<'a, P::Target> {
+        unsafe { (*ptr).try_project(p) }.unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, &'a P::Target> for BytesRef<'b, P::Source>
+where
+    P: AlignedProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: Validate,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a P::Target {
+        unsafe { (*ptr).try_project(p) }
+            .and_then(|place| place.try_get())
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for BytesRef<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: Validate,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target {
+        unsafe { (*ptr).try_project(p) }
+            .and_then(|place| place.try_read())
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+unsafe impl<'a, P, I> PlaceIndex<P, I> for BytesRef<'a, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: IndexProjection<I> + ByteLen,
+    <P::Target as IndexProjection<I>>::Output: ByteLen,
+{
+    type Output = BytesRef<'a, <P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        unsafe { (*ptr).try_project(p) }
+            .and_then(|place| place.try_project(&q))
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+// Every place is checked against the buffer and every read is validated, so all of this is safe
+// with a correct projection.
+unsafe impl<'a, 'b: 'a, P> SafePlaceBorrow<'a, P, BytesRef<'a, P::Target>>
+    for BytesRef<'b, P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: ByteLen,
+{
+}
+unsafe impl<'a, 'b: 'a, P> SafePlaceBorrow<'a, P, &'a P::Target> for BytesRef<'b, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: Validate,
+{
+}
+unsafe impl<P> SafePlaceRead<P> for BytesRef<'_, P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: Validate,
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for BytesRef<'_, P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Source: ByteLen,
+    P::Target: IndexProjection<I> + ByteLen,
+    <P::Target as IndexProjection<I>>::Output: ByteLen,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
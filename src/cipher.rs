@@ -0,0 +1,103 @@
+use std::{
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// Per-envelope value that makes the keystream unique. Must never repeat for a given key.
+pub type Nonce = [u8; 12];
+
+/// A stream cipher: encryption and decryption both xor the data with a keystream that only depends
+/// on the key, the nonce and the position in the data. This is what lets `EncryptedEnvelope`
+/// decrypt and re-encrypt any byte range on its own.
+pub trait Cipher {
+    type Key: Copy;
+    /// Xor `bytes` with the keystream, starting at byte `pos` of the keystream.
+    fn apply_keystream(key: &Self::Key, nonce: &Nonce, pos: u64, bytes: &mut [u8]);
+}
+
+/// Xors every byte with a one-byte key. Trivially broken; only use it for tests.
+pub struct XorCipher;
+impl Cipher for XorCipher {
+    type Key = u8;
+    fn apply_keystream(key: &u8, _nonce: &Nonce, _pos: u64, bytes: &mut [u8]) {
+        for b in bytes.iter_mut() {
+            *b ^= key;
+        }
+    }
+}
+
+/// The ChaCha20 stream cipher, as specified in RFC 8439.
+pub struct ChaCha20;
+impl ChaCha20 {
+    fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(16);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(12);
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(8);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(7);
+    }
+
+    /// Compute the 64 bytes of keystream for block number `counter`.
+    pub fn block(key: &[u8; 32], counter: u32, nonce: &Nonce) -> [u8; 64] {
+        let word = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap());
+        let mut init = [0u32; 16];
+        init[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+        for (i, chunk) in key.chunks(4).enumerate() {
+            init[4 + i] = word(chunk);
+        }
+        init[12] = counter;
+        for (i, chunk) in nonce.chunks(4).enumerate() {
+            init[13 + i] = word(chunk);
+        }
+
+        let mut s = init;
+        for _ in 0..10 {
+            Self::quarter_round(&mut s, 0, 4, 8, 12);
+            Self::quarter_round(&mut s, 1, 5, 9, 13);
+            Self::quarter_round(&mut s, 2, 6, 10, 14);
+            Self::quarter_round(&mut s, 3, 7, 11, 15);
+            Self::quarter_round(&mut s, 0, 5, 10, 15);
+            Self::quarter_round(&mut s, 1, 6, 11, 12);
+            Self::quarter_round(&mut s, 2, 7, 8, 13);
+            Self::quarter_round(&mut s, 3, 4, 9, 14);
+        }
+
+        let mut out = [0u8; 64];
+        for (i, chunk) in out.chunks_mut(4).enumerate() {
+            chunk.copy_from_slice(&s[i].wrapping_add(init[i]).to_le_bytes());
+        }
+        out
+    }
+}
+impl Cipher for ChaCha20 {
+    type Key = [u8; 32];
+    fn apply_keystream(key: &[u8; 32], nonce: &Nonce, mut pos: u64, mut bytes: &mut [u8]) {
+        while !bytes.is_empty() {
+            let counter = u32::try_from(pos / 64).expect("ChaCha20 keystream exhausted");
+            let block = Self::block(key, counter, nonce);
+            let start = (pos % 64) as usize;
+            let len = bytes.len().min(64 - start);
+            let (chunk, rest) = bytes.split_at_mut(len);
+            for (b, k) in chunk.iter_mut().zip(&block[start..]) {
+                *b ^= k;
+            }
+            bytes = rest;
+            pos += len as u64;
+        }
+    }
+}
+
+/// Make a nonce that won't repeat: a process-wide counter plus random bits from the std hasher
+/// seed, so that different processes don't collide either.
+pub fn fresh_nonce() -> Nonce {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let random = RandomState::new().build_hasher().finish();
+    let mut nonce = [0; 12];
+    nonce[..8].copy_from_slice(&random.to_le_bytes());
+    nonce[8..].copy_from_slice(&count.to_le_bytes());
+    nonce
+}
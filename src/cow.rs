@@ -0,0 +1,121 @@
+use std::{cell::UnsafeCell, rc::Rc};
+
+use crate::*;
+
+/// A copy-on-write pointer to a shared `T`. Reads are free, and a write with `p!((*c).a.b = x)`
+/// first makes the value unique like `Rc::make_mut`, so only the first write to a shared value
+/// clones it. Other snapshots of the value don't see the write.
+///
+/// `p!` writes through a shared pointer to the `CowPtr`, so the `Rc` is in an `UnsafeCell` and
+/// there is no `Deref`: a `&T` could see later writes. Use `to_rc` for a snapshot instead.
+pub struct CowPtr<T>(UnsafeCell<Rc<T>>);
+
+impl<T> CowPtr<T> {
+    pub fn new(x: T) -> Self {
+        Self::from(Rc::new(x))
+    }
+    /// Another pointer to the same value, e.g. to keep as a snapshot.
+    pub fn snapshot(&self) -> Self {
+        Self::from(self.to_rc())
+    }
+    /// The current value, shared.
+    pub fn to_rc(&self) -> Rc<T> {
+        self.rc().clone()
+    }
+    /// Whether other pointers share the value, so that the next write clones it.
+    pub fn is_shared(&self) -> bool {
+        Rc::strong_count(self.rc()) > 1 || Rc::weak_count(self.rc()) > 0
+    }
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(self.rc(), other.rc())
+    }
+    pub fn into_rc(self) -> Rc<T> {
+        self.0.into_inner()
+    }
+    fn rc(&self) -> &Rc<T> {
+        // Only writes replace the `Rc`, and they don't keep references to it.
+        unsafe { &*self.0.get() }
+    }
+}
+
+impl<T> From<Rc<T>> for CowPtr<T> {
+    fn from(rc: Rc<T>) -> Self {
+        Self(UnsafeCell::new(rc))
+    }
+}
+impl<T> Clone for CowPtr<T> {
+    fn clone(&self) -> Self {
+        self.snapshot()
+    }
+}
+
+impl<T> HasPlace for CowPtr<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, &'a P::Target> for CowPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a P::Target {
+        let whole = unsafe { Rc::as_ptr((*ptr).rc()) };
+        unsafe { &*p.borrow::<*const _, *const _>(&raw const whole) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for CowPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        let whole = unsafe { Rc::as_ptr((*ptr).rc()) };
+        unsafe { p.read(&raw const whole) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for CowPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Clone,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        // Like `Rc::make_mut`, but `clone` and `drop` may use this `CowPtr` again, so they only
+        // run while we don't hold a `&mut`.
+        let cell = unsafe { (*ptr).0.get() };
+        let whole: *mut P::Source = loop {
+            if let Some(whole) = Rc::get_mut(unsafe { &mut *cell }) {
+                break whole;
+            }
+            let copy = P::Source::clone(unsafe { &*cell });
+            drop(unsafe { cell.replace(Rc::new(copy)) });
+        };
+        let place = unsafe { p.borrow::<*mut _, *mut _>(&raw const whole) };
+        drop(unsafe { place.replace(x) });
+    }
+}
+
+// After cloning the value is ours, so writes are like writes through a `&mut`. References
+// from borrows could be invalidated by a later write, which `p!` doesn't check, so there is no
+// safe borrow.
+unsafe impl<P> SafePlaceRead<P> for CowPtr<P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceWrite<P> for CowPtr<P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Source: Clone,
+{
+}
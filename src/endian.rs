@@ -0,0 +1,198 @@
+use std::{
+    marker::PhantomData,
+    ptr::{NonNull, Pointee},
+};
+
+use crate::*;
+
+/// Plain-data types whose byte order can be swapped. Implemented for integers, floats, arrays of
+/// those, and structs with `#[derive(EndianConvert)]`.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value and the type must have no padding, so that
+/// values can be read from and written to arbitrary byte buffers.
+pub unsafe trait EndianConvert: Copy {
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! impl_endian_convert_int {
+    ($($ty:ty),*) => {
+        $(unsafe impl EndianConvert for $ty {
+            fn swap_bytes(self) -> Self {
+                <$ty>::swap_bytes(self)
+            }
+        })*
+    };
+}
+impl_endian_convert_int!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+unsafe impl EndianConvert for f32 {
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+unsafe impl EndianConvert for f64 {
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
+unsafe impl<T: EndianConvert, const N: usize> EndianConvert for [T; N] {
+    fn swap_bytes(self) -> Self {
+        self.map(T::swap_bytes)
+    }
+}
+
+/// The byte order of the data in an `EndianPtr`.
+pub trait ByteOrder {
+    /// Convert between this byte order and the native one (it's the same in both directions).
+    fn convert<T: EndianConvert>(x: T) -> T;
+}
+pub struct BigEndian;
+pub struct LittleEndian;
+impl ByteOrder for BigEndian {
+    fn convert<T: EndianConvert>(x: T) -> T {
+        if cfg!(target_endian = "big") {
+            x
+        } else {
+            x.swap_bytes()
+        }
+    }
+}
+impl ByteOrder for LittleEndian {
+    fn convert<T: EndianConvert>(x: T) -> T {
+        if cfg!(target_endian = "little") {
+            x
+        } else {
+            x.swap_bytes()
+        }
+    }
+}
+
+/// A pointer to a `T` stored in byte order `O` in a byte buffer. Reads and writes go through
+/// `EndianConvert` and are unaligned, so `p!((*hdr).len)` reads a big-endian `u32` from a network
+/// packet.
+pub struct EndianPtr<'a, T: ?Sized, O> {
+    ptr: NonNull<u8>,
+    meta: <T as Pointee>::Metadata,
+    phantom: PhantomData<(&'a mut [u8], O)>,
+}
+/// A big-endian `T` in a byte buffer.
+pub type Be<'a, T> = EndianPtr<'a, T, BigEndian>;
+/// A little-endian `T` in a byte buffer.
+pub type Le<'a, T> = EndianPtr<'a, T, LittleEndian>;
+
+impl<'a, T: EndianConvert, O: ByteOrder> EndianPtr<'a, T, O> {
+    /// Point to the `T` at the start of `buf`. Panics if `buf` is too small.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        assert!(buf.len() >= size_of::<T>(), "buffer too small");
+        Self {
+            ptr: NonNull::from(buf).cast(),
+            meta: (),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized, O> EndianPtr<'a, T, O> {
+    fn project<P>(&self, p: &P) -> EndianPtr<'a, P::Target, O>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        EndianPtr {
+            ptr: unsafe { self.ptr.add(p.offset(self.meta)) },
+            meta: p.project_metadata(self.meta),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, O> Clone for EndianPtr<'_, T, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized, O> Copy for EndianPtr<'_, T, O> {}
+
+impl<T: ?Sized, O> HasPlace for EndianPtr<'_, T, O> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P, O> PlaceBorrow<'a, P, EndianPtr<'a, P::Target, O>>
+    for EndianPtr<'b, P::Source, O>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> EndianPtr<'a, P::Target, O> {
+        unsafe { (*ptr).project(p) }
+    }
+}
+
+unsafe impl<P, O> PlaceRead<P> for EndianPtr<'_, P::Source, O>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: EndianConvert,
+    O: ByteOrder,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target {
+        let place = unsafe { (*ptr).project(p) };
+        O::convert(unsafe { place.ptr.cast::<P::Target>().read_unaligned() })
+    }
+}
+
+unsafe impl<P, O> PlaceWrite<P> for EndianPtr<'_, P::Source, O>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: EndianConvert,
+    O: ByteOrder,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        let place = unsafe { (*ptr).project(p) };
+        unsafe { place.ptr.cast::<P::Target>().write_unaligned(O::convert(x)) }
+    }
+}
+
+unsafe impl<'a, P, I, O> PlaceIndex<P, I> for EndianPtr<'a, P::Source, O>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = EndianPtr<'a, <P::Target as IndexProjection<I>>::Output, O>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        unsafe { (*ptr).project(p).project(&q) }
+    }
+}
+
+// The pointer always stays in its buffer and any bytes are a valid `EndianConvert` value, so
+// everything is safe with a correct projection.
+unsafe impl<'a, 'b: 'a, P, O> SafePlaceBorrow<'a, P, EndianPtr<'a, P::Target, O>>
+    for EndianPtr<'b, P::Source, O>
+where
+    P: SafeProjection + ?Sized,
+{
+}
+unsafe impl<P, O> SafePlaceRead<P> for EndianPtr<'_, P::Source, O>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: EndianConvert,
+    O: ByteOrder,
+{
+}
+unsafe impl<P, O> SafePlaceWrite<P> for EndianPtr<'_, P::Source, O>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: EndianConvert,
+    O: ByteOrder,
+{
+}
+unsafe impl<P, I, O> SafePlaceIndex<P, I> for EndianPtr<'_, P::Source, O>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
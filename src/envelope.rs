@@ -0,0 +1,432 @@
+use std::{
+    marker::PhantomData,
+    ptr::{NonNull, Pointee},
+};
+
+use crate::*;
+
+/// Number of keystream bytes reserved for the key check, before the encrypted value.
+const KEY_CHECK_LEN: usize = 16;
+
+/// An encrypted `T`. This only holds the encrypted bytes; a key is required to access the `T`
+/// value. Fields can be decrypted and overwritten individually through `EnvelopeBorrow` and
+/// `EnvelopeBorrowMut`, without ever decrypting the rest of the value.
+///
+/// `T` may be unsized, e.g. `[T]` or `str`: the metadata is then stored in clear next to the
+/// ciphertext, and elements or subslices can be decrypted on their own.
+///
+/// Overwriting a field re-encrypts it with the same keystream, so someone who sees the ciphertext
+/// before and after a write learns the xor of the old and new values of that field.
+pub struct EncryptedEnvelope<T: ?Sized, C: Cipher = ChaCha20> {
+    bytes: Box<[u8]>,
+    meta: <T as Pointee>::Metadata,
+    nonce: Nonce,
+    // The first keystream bytes, used to check that the key is the right one.
+    key_check: [u8; KEY_CHECK_LEN],
+    phantom: PhantomData<(C, Box<T>)>,
+}
+
+/// Types that can be put in an `EncryptedEnvelope`: values that can be copied byte by byte, and
+/// rebuilt from those bytes once decrypted.
+///
+/// # Safety
+///
+/// `byte_len` must return the size of a value with that metadata, and all those bytes must be
+/// initialized.
+pub unsafe trait EnvelopeData {
+    /// What we get when decrypting a `Self`.
+    type Owned;
+    /// Number of bytes of a value with the given metadata.
+    fn byte_len(meta: <Self as Pointee>::Metadata) -> usize;
+    /// Rebuild a value from its bytes, which are `byte_len(meta)` long.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be those of a valid `Self` with metadata `meta`.
+    unsafe fn from_bytes(bytes: &[u8], meta: <Self as Pointee>::Metadata) -> Self::Owned;
+}
+unsafe impl<T: NoUninit> EnvelopeData for T {
+    type Owned = T;
+    fn byte_len(_: ()) -> usize {
+        size_of::<T>()
+    }
+    unsafe fn from_bytes(bytes: &[u8], _: ()) -> T {
+        unsafe { bytes.as_ptr().cast::<T>().read_unaligned() }
+    }
+}
+unsafe impl<T: NoUninit> EnvelopeData for [T] {
+    type Owned = Vec<T>;
+    fn byte_len(len: usize) -> usize {
+        len * size_of::<T>()
+    }
+    unsafe fn from_bytes(bytes: &[u8], len: usize) -> Vec<T> {
+        let mut v: Vec<T> = Vec::with_capacity(len);
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), v.as_mut_ptr().cast(), bytes.len());
+            v.set_len(len);
+        }
+        v
+    }
+}
+unsafe impl EnvelopeData for str {
+    type Owned = String;
+    fn byte_len(len: usize) -> usize {
+        len
+    }
+    /// Panics if the bytes are not UTF-8, e.g. because a range projection cut a char in two.
+    unsafe fn from_bytes(bytes: &[u8], _: usize) -> String {
+        String::from_utf8(bytes.to_vec()).expect("decrypted bytes are not valid UTF-8")
+    }
+}
+
+/// A shared borrow of a part of an `EncryptedEnvelope`. Reading from it decrypts only the bytes
+/// of that part.
+pub struct EnvelopeBorrow<'a, Whole: ?Sized, Part: ?Sized, C: Cipher = ChaCha20> {
+    env: &'a EncryptedEnvelope<Whole, C>,
+    key: C::Key,
+    proj: ErasedProj<Whole, Part>,
+}
+
+/// Like `EnvelopeBorrow` but allows writing.
+pub struct EnvelopeBorrowMut<'a, Whole: ?Sized, Part: ?Sized, C: Cipher = ChaCha20> {
+    // Raw pointer so that reborrows can be made from a `*const Self`.
+    env: NonNull<EncryptedEnvelope<Whole, C>>,
+    key: C::Key,
+    proj: ErasedProj<Whole, Part>,
+    phantom: PhantomData<&'a mut EncryptedEnvelope<Whole, C>>,
+}
+
+impl<T: NoUninit, C: Cipher> EncryptedEnvelope<T, C> {
+    /// Encrypt `x` with a fresh nonce. `T: NoUninit` because encrypting reads all its bytes,
+    /// and decrypting makes copies of the value.
+    pub fn encrypt(x: T, key: C::Key) -> Self {
+        Self::encrypt_copy(&x, key)
+    }
+    /// Encrypt `x` with the given nonce, which must never have been used with this key.
+    pub fn encrypt_with_nonce(x: T, key: C::Key, nonce: Nonce) -> Self {
+        Self::encrypt_copy_with_nonce(&x, key, nonce)
+    }
+}
+
+impl<T: EnvelopeData + ?Sized, C: Cipher> EncryptedEnvelope<T, C> {
+    /// Encrypt a copy of `x` with a fresh nonce. Works for unsized values like `[T]` and `str`.
+    pub fn encrypt_copy(x: &T, key: C::Key) -> Self {
+        Self::encrypt_copy_with_nonce(x, key, fresh_nonce())
+    }
+    /// Encrypt a copy of `x` with the given nonce, which must never have been used with this key.
+    pub fn encrypt_copy_with_nonce(x: &T, key: C::Key, nonce: Nonce) -> Self {
+        let mut key_check = [0; KEY_CHECK_LEN];
+        C::apply_keystream(&key, &nonce, 0, &mut key_check);
+        let meta = std::ptr::metadata(x);
+        let len = T::byte_len(meta);
+        // Copy the bytes of `x`.
+        let mut b: Box<[u8]> =
+            unsafe { std::slice::from_raw_parts(std::ptr::from_ref(x).cast::<u8>(), len).into() };
+        C::apply_keystream(&key, &nonce, KEY_CHECK_LEN as u64, &mut b);
+        Self {
+            bytes: b,
+            meta,
+            nonce,
+            key_check,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Decrypt the whole value. Panics if the key is wrong.
+    pub fn decrypt(&self, key: C::Key) -> T::Owned {
+        self.check_key(&key);
+        unsafe { self.read_part(&ErasedProj::identity(self.meta), key) }
+    }
+}
+
+impl<T: ?Sized, C: Cipher> EncryptedEnvelope<T, C> {
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    /// Metadata of the encrypted value, e.g. the length of a slice.
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.meta
+    }
+
+    /// Check the key is the right one (otherwise we risk creating an invalid `T`).
+    fn check_key(&self, key: &C::Key) {
+        let mut key_check = [0; KEY_CHECK_LEN];
+        C::apply_keystream(key, &self.nonce, 0, &mut key_check);
+        assert!(key_check == self.key_check, "wrong key for this envelope");
+    }
+
+    /// Decrypt only the `len` bytes that start at byte `offset`.
+    ///
+    /// # Safety
+    ///
+    /// The key must have been checked.
+    unsafe fn decrypt_bytes(&self, offset: usize, len: usize, key: C::Key) -> Box<[u8]> {
+        let mut b: Box<[u8]> = self.bytes[offset..offset + len].into();
+        C::apply_keystream(&key, &self.nonce, (KEY_CHECK_LEN + offset) as u64, &mut b);
+        b
+    }
+
+    /// Decrypt only the `U` that starts at byte `offset`.
+    ///
+    /// # Safety
+    ///
+    /// There must be a `U` at that offset in `T`, and the key must have been checked.
+    unsafe fn read_at<U>(&self, offset: usize, key: C::Key) -> U {
+        let b = unsafe { self.decrypt_bytes(offset, size_of::<U>(), key) };
+        unsafe { b.as_ptr().cast::<U>().read_unaligned() }
+    }
+
+    /// Decrypt only the part of `T` that `proj` points to.
+    ///
+    /// # Safety
+    ///
+    /// `proj` must be correct for `T`, and the key must have been checked.
+    unsafe fn read_part<U>(&self, proj: &ErasedProj<T, U>, key: C::Key) -> U::Owned
+    where
+        U: EnvelopeData + ?Sized,
+    {
+        let len = U::byte_len(proj.metadata());
+        unsafe {
+            let b = self.decrypt_bytes(proj.byte_offset(), len, key);
+            U::from_bytes(&b, proj.metadata())
+        }
+    }
+
+    /// Overwrite the `U` that starts at byte `offset`, touching only its bytes. With a stream
+    /// cipher we don't need the old plaintext to do that.
+    ///
+    /// # Safety
+    ///
+    /// There must be a `U` at that offset in `T`, and the key must have been checked.
+    unsafe fn write_at<U>(&mut self, offset: usize, x: U, key: C::Key) {
+        let bytes = &mut self.bytes[offset..offset + size_of::<U>()];
+        unsafe { bytes.as_mut_ptr().cast::<U>().write_unaligned(x) };
+        C::apply_keystream(&key, &self.nonce, (KEY_CHECK_LEN + offset) as u64, bytes);
+    }
+
+    /// Borrow the value. Panics if the key is wrong.
+    pub fn borrow(&self, key: C::Key) -> EnvelopeBorrow<'_, T, T, C> {
+        self.check_key(&key);
+        EnvelopeBorrow {
+            env: self,
+            key,
+            proj: ErasedProj::identity(self.meta),
+        }
+    }
+
+    /// Borrow the value mutably. Panics if the key is wrong.
+    pub fn borrow_mut(&mut self, key: C::Key) -> EnvelopeBorrowMut<'_, T, T, C> {
+        self.check_key(&key);
+        EnvelopeBorrowMut {
+            proj: ErasedProj::identity(self.meta),
+            env: NonNull::from(self),
+            key,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, Whole, Part, C> EnvelopeBorrow<'a, Whole, Part, C>
+where
+    Whole: ?Sized,
+    Part: EnvelopeData + ?Sized,
+    C: Cipher,
+{
+    /// Decrypt only the bytes of `Part`.
+    pub fn decrypt(&self) -> Part::Owned {
+        unsafe { self.env.read_part(&self.proj, self.key) }
+    }
+}
+
+impl<'a, Whole, Part, C> EnvelopeBorrowMut<'a, Whole, Part, C>
+where
+    Whole: ?Sized,
+    Part: EnvelopeData + ?Sized,
+    C: Cipher,
+{
+    /// Decrypt only the bytes of `Part`.
+    pub fn decrypt(&self) -> Part::Owned {
+        unsafe { self.env.as_ref().read_part(&self.proj, self.key) }
+    }
+}
+
+impl<T: ?Sized, C: Cipher> HasPlace for EncryptedEnvelope<T, C> {
+    type Target = T;
+}
+impl<'a, Whole: ?Sized, Part: ?Sized, C: Cipher> HasPlace for EnvelopeBorrow<'a, Whole, Part, C> {
+    type Target = Part;
+}
+impl<'a, Whole: ?Sized, Part: ?Sized, C: Cipher> HasPlace
+    for EnvelopeBorrowMut<'a, Whole, Part, C>
+{
+    type Target = Part;
+}
+
+unsafe impl<'a, 'b: 'a, Whole, P, C> PlaceBorrow<'a, P, EnvelopeBorrow<'a, Whole, P::Target, C>>
+    for EnvelopeBorrow<'b, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    C: Cipher,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> EnvelopeBorrow<'a, Whole, P::Target, C> {
+        let this = unsafe { &*ptr };
+        EnvelopeBorrow {
+            env: this.env,
+            key: this.key,
+            proj: this.proj.then(p),
+        }
+    }
+}
+unsafe impl<'a, 'b: 'a, Whole, P, C> PlaceBorrow<'a, P, EnvelopeBorrowMut<'a, Whole, P::Target, C>>
+    for EnvelopeBorrowMut<'b, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    C: Cipher,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Unique;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> EnvelopeBorrowMut<'a, Whole, P::Target, C> {
+        let this = unsafe { &*ptr };
+        EnvelopeBorrowMut {
+            env: this.env,
+            key: this.key,
+            proj: this.proj.then(p),
+            phantom: PhantomData,
+        }
+    }
+}
+
+unsafe impl<'a, Whole, P, C> PlaceRead<P> for EnvelopeBorrow<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    C: Cipher,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe {
+            let this = &*ptr;
+            this.env.read_at(this.proj.then(p).byte_offset(), this.key)
+        }
+    }
+}
+unsafe impl<'a, Whole, P, C> PlaceRead<P> for EnvelopeBorrowMut<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    C: Cipher,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe {
+            let this = &*ptr;
+            let offset = this.proj.then(p).byte_offset();
+            this.env.as_ref().read_at(offset, this.key)
+        }
+    }
+}
+
+unsafe impl<'a, Whole, P, C> PlaceWrite<P> for EnvelopeBorrowMut<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: NoUninit,
+    C: Cipher,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        unsafe {
+            let this = &*ptr;
+            let offset = this.proj.then(p).byte_offset();
+            (*this.env.as_ptr()).write_at(offset, x, this.key)
+        }
+    }
+}
+
+unsafe impl<'a, Whole, P, I, C> PlaceIndex<P, I> for EnvelopeBorrow<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    C: Cipher,
+{
+    type Output = EnvelopeBorrow<'a, Whole, <P::Target as IndexProjection<I>>::Output, C>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let this = unsafe { &*ptr };
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        EnvelopeBorrow {
+            env: this.env,
+            key: this.key,
+            proj: this.proj.then(p).then(&q),
+        }
+    }
+}
+unsafe impl<'a, Whole, P, I, C> PlaceIndex<P, I> for EnvelopeBorrowMut<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    C: Cipher,
+{
+    type Output = EnvelopeBorrowMut<'a, Whole, <P::Target as IndexProjection<I>>::Output, C>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let this = unsafe { &*ptr };
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        EnvelopeBorrowMut {
+            env: this.env,
+            key: this.key,
+            proj: this.proj.then(p).then(&q),
+            phantom: PhantomData,
+        }
+    }
+}
+
+// Borrowing only composes projections, reads and writes are in-bounds for any correct projection,
+// and we never hand out references to the plaintext.
+unsafe impl<'a, 'b: 'a, Whole, P, C> SafePlaceBorrow<'a, P, EnvelopeBorrow<'a, Whole, P::Target, C>>
+    for EnvelopeBorrow<'b, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: SafeProjection + ?Sized,
+    C: Cipher,
+{
+}
+unsafe impl<'a, Whole, P, I, C> SafePlaceIndex<P, I> for EnvelopeBorrow<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+    C: Cipher,
+{
+}
+unsafe impl<'a, Whole, P, C> SafePlaceRead<P> for EnvelopeBorrow<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: SafeProjection + ?Sized,
+    C: Cipher,
+    P::Target: Copy,
+{
+}
+unsafe impl<'a, Whole, P, C> SafePlaceRead<P> for EnvelopeBorrowMut<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: SafeProjection + ?Sized,
+    C: Cipher,
+    P::Target: Copy,
+{
+}
+unsafe impl<'a, Whole, P, C> SafePlaceWrite<P> for EnvelopeBorrowMut<'a, Whole, P::Source, C>
+where
+    Whole: ?Sized,
+    P: SafeProjection + ?Sized,
+    C: Cipher,
+    P::Target: NoUninit,
+{
+}
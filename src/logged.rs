@@ -0,0 +1,242 @@
+use std::{cell::RefCell, fmt, ops::Range, ptr::Pointee, rc::Rc};
+
+use crate::*;
+
+/// The place operations that `Logged` reports.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlaceOp {
+    Borrow,
+    Read,
+    Write,
+    Deref,
+}
+
+/// A place operation done through a `Logged` pointer. `path` and `bytes` are relative to the
+/// place the first `Logged` pointer of the chain pointed to.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlaceEvent {
+    pub op: PlaceOp,
+    pub path: PlacePath,
+    pub bytes: Range<usize>,
+    /// The `BORROW_KIND` of the wrapped pointer's `PlaceBorrow` impl, for borrows.
+    pub borrow_kind: Option<BorrowKind>,
+}
+
+impl fmt::Display for PlaceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} {:?}", self.op, self.path, self.bytes)?;
+        if let Some(kind) = &self.borrow_kind {
+            write!(f, " ({kind:?})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where `Logged` pointers send their events.
+pub trait PlaceSink {
+    fn event(&self, event: PlaceEvent);
+}
+impl<F: Fn(PlaceEvent)> PlaceSink for F {
+    fn event(&self, event: PlaceEvent) {
+        self(event)
+    }
+}
+
+/// A `PlaceSink` that keeps the events in memory.
+#[derive(Default, Debug)]
+pub struct EventLog(RefCell<Vec<PlaceEvent>>);
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn events(&self) -> Vec<PlaceEvent> {
+        self.0.borrow().clone()
+    }
+    /// Return the events and start over.
+    pub fn take(&self) -> Vec<PlaceEvent> {
+        self.0.take()
+    }
+}
+impl PlaceSink for EventLog {
+    fn event(&self, event: PlaceEvent) {
+        self.0.borrow_mut().push(event);
+    }
+}
+
+/// Wraps any pointer `X` and does its place operations through `X`'s impls, reporting each one to
+/// a `PlaceSink` first. Borrows give `Logged` pointers that report to the same sink, so existing
+/// pointers like `EnvelopeBorrow` can be audited without changing them.
+///
+/// Derefs are reported, but the pointer they return is the one stored in the place, so
+/// operations through it aren't.
+pub struct Logged<X: HasPlace + ?Sized> {
+    sink: Rc<dyn PlaceSink>,
+    path: PlacePath,
+    offset: usize,
+    meta: <X::Target as Pointee>::Metadata,
+    inner: X,
+}
+
+impl<X: HasPlace> Logged<X> {
+    pub fn new(inner: X, sink: Rc<dyn PlaceSink>) -> Self
+    where
+        X::Target: Sized,
+    {
+        // Sized places have no metadata to get wrong.
+        unsafe { Self::with_metadata(inner, (), sink) }
+    }
+    /// For pointers to unsized places, whose metadata we can't get from `X`.
+    ///
+    /// # Safety
+    ///
+    /// `meta` must be the metadata of the place `inner` points to, so that its size can be
+    /// computed.
+    pub unsafe fn with_metadata(
+        inner: X,
+        meta: <X::Target as Pointee>::Metadata,
+        sink: Rc<dyn PlaceSink>,
+    ) -> Self {
+        Self {
+            sink,
+            path: PlacePath::new(),
+            offset: 0,
+            meta,
+            inner,
+        }
+    }
+    pub fn inner(&self) -> &X {
+        &self.inner
+    }
+    pub fn into_inner(self) -> X {
+        self.inner
+    }
+    /// The path of this place, relative to the first `Logged` pointer.
+    pub fn path(&self) -> &PlacePath {
+        &self.path
+    }
+
+    /// The path and byte range of the target of `p`.
+    fn locate<P>(&self, p: &P) -> (PlacePath, Range<usize>)
+    where
+        P: NamedProjection<Source = X::Target> + ?Sized,
+    {
+        let mut path = self.path.clone();
+        p.push_path(&mut path);
+        let start = self.offset + p.offset(self.meta);
+        let meta = p.project_metadata(self.meta);
+        let target: *const P::Target = std::ptr::from_raw_parts(std::ptr::null::<()>(), meta);
+        // The metadata is valid, as promised to `with_metadata` and then projected by `p`.
+        let len = unsafe { std::mem::size_of_val_raw(target) };
+        let end = start
+            .checked_add(len)
+            .expect("place ends past `usize::MAX`");
+        (path, start..end)
+    }
+    fn report<P>(&self, op: PlaceOp, p: &P, borrow_kind: Option<BorrowKind>)
+    where
+        P: NamedProjection<Source = X::Target> + ?Sized,
+    {
+        let (path, bytes) = self.locate(p);
+        self.sink.event(PlaceEvent {
+            op,
+            path,
+            bytes,
+            borrow_kind,
+        });
+    }
+}
+
+impl<X: HasPlace> HasPlace for Logged<X> {
+    type Target = X::Target;
+}
+
+unsafe impl<'a, P, X, Y> PlaceBorrow<'a, P, Logged<Y>> for Logged<X>
+where
+    P: NamedProjection + ?Sized,
+    X: PlaceBorrow<'a, P, Y>,
+    Y: HasPlace<Target = P::Target>,
+{
+    const BORROW_KIND: BorrowKind = X::BORROW_KIND;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> Logged<Y> {
+        let this = unsafe { &*ptr };
+        this.report(PlaceOp::Borrow, p, Some(X::BORROW_KIND));
+        let (path, bytes) = this.locate(p);
+        Logged {
+            sink: this.sink.clone(),
+            path,
+            offset: bytes.start,
+            meta: p.project_metadata(this.meta),
+            inner: unsafe { X::borrow(&raw const this.inner, p) },
+        }
+    }
+}
+
+unsafe impl<P, X> PlaceRead<P> for Logged<X>
+where
+    P: NamedProjection + ?Sized,
+    X: PlaceRead<P>,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        let this = unsafe { &*ptr };
+        this.report(PlaceOp::Read, p, None);
+        unsafe { X::read(&raw const this.inner, p) }
+    }
+}
+
+unsafe impl<P, X> PlaceWrite<P> for Logged<X>
+where
+    P: NamedProjection + ?Sized,
+    X: PlaceWrite<P>,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).report(PlaceOp::Write, p, None) };
+        unsafe { X::write(&raw mut (*ptr).inner, p, x) }
+    }
+}
+
+unsafe impl<P, X> PlaceDeref<P> for Logged<X>
+where
+    P: NamedProjection + ?Sized,
+    P::Target: HasPlace,
+    X: PlaceDeref<P>,
+{
+    unsafe fn double_deref(ptr: *mut Self, p: &P) -> *const P::Target {
+        unsafe { (*ptr).report(PlaceOp::Deref, p, None) };
+        unsafe { X::double_deref(&raw mut (*ptr).inner, p) }
+    }
+}
+
+// Reporting doesn't touch the place, so these are as safe as the wrapped impls.
+unsafe impl<'a, P, X, Y> SafePlaceBorrow<'a, P, Logged<Y>> for Logged<X>
+where
+    P: NamedProjection + SafeProjection + ?Sized,
+    X: SafePlaceBorrow<'a, P, Y>,
+    Y: HasPlace<Target = P::Target>,
+{
+}
+unsafe impl<P, X> SafePlaceRead<P> for Logged<X>
+where
+    P: NamedProjection + SafeProjection + ?Sized,
+    X: SafePlaceRead<P>,
+{
+}
+unsafe impl<P, X> SafePlaceWrite<P> for Logged<X>
+where
+    P: NamedProjection + SafeProjection + ?Sized,
+    X: SafePlaceWrite<P>,
+{
+}
+unsafe impl<P, X> SafePlaceDeref<P> for Logged<X>
+where
+    P: NamedProjection + SafeProjection + ?Sized,
+    P::Target: HasPlace,
+    X: SafePlaceDeref<P>,
+{
+}
@@ -0,0 +1,252 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    fs::File,
+    io,
+    os::fd::AsRawFd,
+    ptr::{NonNull, Pointee},
+};
+
+use crate::*;
+
+/// A file mapped in memory, shared with the file. Writes through `MmapPtr` record which pages
+/// they touched, so that `flush` only syncs those.
+pub struct Mmap {
+    ptr: NonNull<u8>,
+    len: usize,
+    page_size: usize,
+    dirty: RefCell<BTreeSet<usize>>,
+}
+
+impl Mmap {
+    /// Map the whole file for reading and writing.
+    pub fn map(file: &File) -> io::Result<Self> {
+        let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot map an empty file",
+            ));
+        }
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: NonNull::new(ptr.cast()).unwrap(),
+            len,
+            page_size: unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize,
+            dirty: Default::default(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Pointer to the `T` at byte `offset`. Panics if it doesn't fit in the file.
+    pub fn at<T>(&self, offset: usize) -> MmapPtr<'_, T> {
+        self.at_raw_parts(offset, ())
+    }
+    /// Pointer to the `[T]` of length `len` at byte `offset`. Panics if it doesn't fit in the file.
+    pub fn slice_at<T>(&self, offset: usize, len: usize) -> MmapPtr<'_, [T]> {
+        self.at_raw_parts(offset, len)
+    }
+    fn at_raw_parts<T: ?Sized>(
+        &self,
+        offset: usize,
+        meta: <T as Pointee>::Metadata,
+    ) -> MmapPtr<'_, T> {
+        let ptr = MmapPtr {
+            map: self,
+            offset,
+            meta,
+        };
+        assert!(
+            offset
+                .checked_add(ptr.size())
+                .is_some_and(|end| end <= self.len),
+            "pointer out of the mapped file"
+        );
+        ptr
+    }
+
+    /// Indices of the pages written to since the last `flush`.
+    pub fn dirty_pages(&self) -> Vec<usize> {
+        self.dirty.borrow().iter().copied().collect()
+    }
+
+    fn mark_dirty(&self, offset: usize, size: usize) {
+        if size == 0 {
+            return;
+        }
+        let pages = offset / self.page_size..=(offset + size - 1) / self.page_size;
+        self.dirty.borrow_mut().extend(pages);
+    }
+
+    /// Write the dirty pages back to the file, one `msync` per run of consecutive pages.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut dirty = self.dirty.borrow_mut();
+        let mut pages = dirty.iter().copied().peekable();
+        while let Some(first) = pages.next() {
+            let mut last = first;
+            while pages.next_if_eq(&(last + 1)).is_some() {
+                last += 1;
+            }
+            let start = first * self.page_size;
+            let end = ((last + 1) * self.page_size).min(self.len);
+            let ret = unsafe {
+                libc::msync(
+                    self.ptr.as_ptr().add(start).cast(),
+                    end - start,
+                    libc::MS_SYNC,
+                )
+            };
+            if ret != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        dirty.clear();
+        Ok(())
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+    }
+}
+
+/// A pointer to a `T` inside an `Mmap`. Fields in on-disk formats are often misaligned, so reads
+/// and writes are unaligned and the pointer never hands out references. Written values end up
+/// in the file, so they must be `NoUninit`.
+pub struct MmapPtr<'a, T: ?Sized> {
+    map: &'a Mmap,
+    offset: usize,
+    meta: <T as Pointee>::Metadata,
+}
+
+impl<'a, T: ?Sized> MmapPtr<'a, T> {
+    /// Byte offset in the file.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.meta
+    }
+    fn as_ptr(&self) -> *mut T {
+        let ptr = unsafe { self.map.ptr.as_ptr().add(self.offset) };
+        std::ptr::from_raw_parts_mut(ptr, self.meta)
+    }
+    fn size(&self) -> usize {
+        unsafe { std::mem::size_of_val_raw(self.as_ptr()) }
+    }
+    fn project<P>(&self, p: &P) -> MmapPtr<'a, P::Target>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        MmapPtr {
+            map: self.map,
+            offset: self.offset + p.offset(self.meta),
+            meta: p.project_metadata(self.meta),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> MmapPtr<'a, [T; N]> {
+    /// Forget the length of the array, like the `[T; N]` to `[T]` unsizing coercion.
+    pub fn unsize(self) -> MmapPtr<'a, [T]> {
+        MmapPtr {
+            map: self.map,
+            offset: self.offset,
+            meta: N,
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for MmapPtr<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for MmapPtr<'_, T> {}
+
+impl<T: ?Sized> HasPlace for MmapPtr<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, MmapPtr<'a, P::Target>> for MmapPtr<'b, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> MmapPtr<'a, P::Target> {
+        unsafe { (*ptr).project(p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for MmapPtr<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).as_ptr().read_unaligned() }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for MmapPtr<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: NoUninit,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        unsafe {
+            let place = (*ptr).project(p);
+            place.as_ptr().write_unaligned(x);
+            place.map.mark_dirty(place.offset, size_of::<P::Target>());
+        }
+    }
+}
+
+unsafe impl<'a, P, I> PlaceIndex<P, I> for MmapPtr<'a, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = MmapPtr<'a, <P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        unsafe { (*ptr).project(p).project(&q) }
+    }
+}
+
+// Borrowing and indexing only compute offsets, so they're fine for any correct projection.
+unsafe impl<'a, 'b: 'a, P> SafePlaceBorrow<'a, P, MmapPtr<'a, P::Target>> for MmapPtr<'b, P::Source> where
+    P: SafeProjection + ?Sized
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for MmapPtr<'_, P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
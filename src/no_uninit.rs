@@ -0,0 +1,18 @@
+/// Plain-data types without uninitialized bytes, so that the bytes of a value can be read as
+/// `[u8]`, e.g. to encrypt them. Implemented for integers, floats, `bool`, `char`, arrays of
+/// those, and structs with `#[derive(NoUninit)]`.
+///
+/// # Safety
+///
+/// The type must have no padding, and no fields like `MaybeUninit` that may be uninitialized.
+pub unsafe trait NoUninit: Copy {}
+
+macro_rules! impl_no_uninit {
+    ($($ty:ty),*) => {
+        $(unsafe impl NoUninit for $ty {})*
+    };
+}
+impl_no_uninit!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char
+);
+unsafe impl<T: NoUninit, const N: usize> NoUninit for [T; N] {}
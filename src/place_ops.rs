@@ -5,6 +5,15 @@ pub trait HasPlace {
 }
 
 /// Borrow a subplace.
+///
+/// # Safety
+///
+/// `borrow` must return a pointer to the target of `p` that allows the accesses `BORROW_KIND`
+/// promises.
+#[diagnostic::on_unimplemented(
+    message = "cannot borrow a subplace of `{Self}` as `{X}`",
+    label = "borrow requires `PlaceBorrow<{P}, {X}>`"
+)]
 pub unsafe trait PlaceBorrow<'a, P, X>
 where
     P: Projection + ?Sized,
@@ -15,10 +24,15 @@ where
     /// borrows are allowed.
     const BORROW_KIND: BorrowKind;
 
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self`, `p` must be correct for its target, and the result
+    /// must be used according to `BORROW_KIND`.
     unsafe fn borrow(ptr: *const Self, p: &P) -> X;
 }
 
 #[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BorrowKind {
     /// Other borrows are allowed (like `*mut T` and `RcRef<T>`).
     Untracked,
@@ -35,28 +49,57 @@ pub enum BorrowKind {
 }
 
 /// Read a value from a subplace.
+///
+/// # Safety
+///
+/// `read` must return the value of the target of `p`.
+#[diagnostic::on_unimplemented(
+    message = "cannot read from a subplace of `{Self}`",
+    label = "read requires `PlaceRead<{P}>`"
+)]
 pub unsafe trait PlaceRead<P>
 where
     P: Projection + ?Sized,
     Self: HasPlace<Target = P::Source>,
 {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self` and `p` must be correct for its target. Unless `Self:
+    /// PlaceMove<P>`, the value must be `Copy` or not be used as an owned value.
     unsafe fn read(ptr: *const Self, p: &P) -> P::Target
     where
         P::Target: Sized;
 }
 
 /// Write to a subplace.
+///
+/// # Safety
+///
+/// `write` must store `x` in the target of `p`, and only there.
+#[diagnostic::on_unimplemented(
+    message = "cannot write to a subplace of `{Self}`",
+    label = "write requires `PlaceWrite<{P}>`"
+)]
 pub unsafe trait PlaceWrite<P>
 where
     P: Projection + ?Sized,
     Self: HasPlace<Target = P::Source>,
 {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self`, `p` must be correct for its target, and nothing may
+    /// be borrowing the target.
     unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
     where
         P::Target: Sized;
 }
 
 /// Allows moving a value out of a subplace. This uses `PlaceRead::read` to read the value.
+///
+/// # Safety
+///
+/// The value returned by `read` must be owned by the caller, who will treat the target as moved
+/// out of.
 pub unsafe trait PlaceMove<P>: PlaceRead<P>
 where
     P: Projection + ?Sized,
@@ -65,16 +108,150 @@ where
 
 /// Allows dereferencing a subplace that contains a pointer. The returned pointer will only be used
 /// for further place operations.
+///
+/// # Safety
+///
+/// `double_deref` must return the pointer stored in the target of `p`.
+#[diagnostic::on_unimplemented(
+    message = "cannot dereference a subplace of `{Self}`",
+    label = "deref requires `PlaceDeref<{P}>`"
+)]
 pub unsafe trait PlaceDeref<P>
 where
     P: Projection + ?Sized,
     P::Target: HasPlace,
     Self: HasPlace<Target = P::Source>,
 {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self` and `p` must be correct for its target.
     unsafe fn double_deref(ptr: *mut Self, p: &P) -> *const P::Target;
 }
 
+/// Index into a subplace, like `Index`/`IndexMut` do for values. The returned pointer will only be
+/// used for further place operations, so borrowing, reading and writing at an index all go through
+/// the usual traits on `Output`.
+///
+/// Pointers to places that can be indexed in-place (see `IndexProjection`) usually return the same
+/// kind of pointer to the element; pointers whose elements live elsewhere (like a `Vec` buffer or an
+/// arena slot) can return any derived pointer.
+///
+/// # Safety
+///
+/// `index` must return a pointer to the element at `idx` of the target of `p`.
+#[diagnostic::on_unimplemented(
+    message = "cannot index a subplace of `{Self}` with `{I}`",
+    label = "indexing requires `PlaceIndex<{P}, {I}>`"
+)]
+pub unsafe trait PlaceIndex<P, I>
+where
+    P: Projection + ?Sized,
+    Self: HasPlace<Target = P::Source>,
+{
+    type Output: HasPlace;
+
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self`, `p` must be correct for its target and `idx` must be
+    /// in bounds, unless the impl checks it.
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output;
+}
+
+/// Marker for `PlaceBorrow` impls that are sound for any `SafeProjection` when `ptr` comes from a
+/// shared reference. This is what `p!(safe ...)` requires.
+///
+/// # Safety
+///
+/// Calling `borrow` like that must be sound, and so must using the result as `BORROW_KIND`
+/// allows for the lifetime `'a`.
+#[diagnostic::on_unimplemented(
+    message = "cannot safely borrow a subplace of `{Self}` as `{X}`",
+    label = "safe borrow requires `SafePlaceBorrow<{P}, {X}>`",
+    note = "use `unsafe {{ p!(...) }}` instead"
+)]
+pub unsafe trait SafePlaceBorrow<'a, P, X>: PlaceBorrow<'a, P, X>
+where
+    P: SafeProjection + ?Sized,
+    X: HasPlace<Target = P::Target>,
+{
+}
+
+/// Marker for `PlaceRead` impls that are sound for any `SafeProjection` when `ptr` comes from a
+/// shared reference. In particular reading must not duplicate ownership of the value.
+///
+/// # Safety
+///
+/// Calling `read` like that must be sound.
+#[diagnostic::on_unimplemented(
+    message = "cannot safely read from a subplace of `{Self}`",
+    label = "safe read requires `SafePlaceRead<{P}>`",
+    note = "use `unsafe {{ p!(...) }}` instead"
+)]
+pub unsafe trait SafePlaceRead<P>: PlaceRead<P>
+where
+    P: SafeProjection + ?Sized,
+{
+}
+
+/// Marker for `PlaceWrite` impls that are sound for any `SafeProjection` when `ptr` comes from a
+/// shared reference.
+///
+/// # Safety
+///
+/// Calling `write` like that must be sound, even if other pointers to the place exist.
+#[diagnostic::on_unimplemented(
+    message = "cannot safely write to a subplace of `{Self}`",
+    label = "safe write requires `SafePlaceWrite<{P}>`",
+    note = "use `unsafe {{ p!(...) }}` instead"
+)]
+pub unsafe trait SafePlaceWrite<P>: PlaceWrite<P>
+where
+    P: SafeProjection + ?Sized,
+{
+}
+
+/// Marker for `PlaceDeref` impls that are sound for any `SafeProjection` when `ptr` comes from a
+/// shared reference, and whose returned pointer can be turned into a shared reference that lives as
+/// long as that one.
+///
+/// # Safety
+///
+/// Calling `double_deref` like that must be sound, and so must turning the result into a
+/// shared reference.
+#[diagnostic::on_unimplemented(
+    message = "cannot safely dereference a subplace of `{Self}`",
+    label = "safe deref requires `SafePlaceDeref<{P}>`",
+    note = "use `unsafe {{ p!(...) }}` instead"
+)]
+pub unsafe trait SafePlaceDeref<P>: PlaceDeref<P>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: HasPlace,
+{
+}
+
+/// Marker for `PlaceIndex` impls that are sound for any `SafeProjection` when `ptr` comes from a
+/// shared reference. Out-of-bounds indices must panic.
+///
+/// # Safety
+///
+/// Calling `index` like that must be sound for any `idx`.
+#[diagnostic::on_unimplemented(
+    message = "cannot safely index a subplace of `{Self}` with `{I}`",
+    label = "safe indexing requires `SafePlaceIndex<{P}, {I}>`",
+    note = "use `unsafe {{ p!(...) }}` instead"
+)]
+pub unsafe trait SafePlaceIndex<P, I>: PlaceIndex<P, I>
+where
+    P: SafeProjection + ?Sized,
+{
+}
+
 /// Drop the contents of a subplace.
+///
+/// # Safety
+///
+/// `drop` must drop the target of `p` in place.
 pub unsafe trait PlaceDrop<P>
 where
     P: Projection + ?Sized,
@@ -82,18 +259,36 @@ where
     Self: HasPlace<Target = P::Source>,
 {
     /// Should call `drop_in_place` on the subplace.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self`, `p` must be correct for its target, and the target
+    /// must be initialized and not used again.
     unsafe fn drop(ptr: *mut Self, p: &P);
 }
 
 /// Clean up a pointer whose contained place has been moved out of/dropped.
+///
+/// # Safety
+///
+/// `drop_husk` must not drop the place.
 pub unsafe trait DropHusk: HasPlace {
     /// Drop the pointer but not the contents of the place (borrowck takes care of that).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `Self` whose place was moved out of or dropped, and it must
+    /// not be used again.
     unsafe fn drop_husk(ptr: *mut Self);
 }
 
 /// If at a coercion site an expression `e` has type `T` but type `U` was expected, and `T:
 /// HasPlace` and `T::Target: PlaceCoerce<T>`, then we replace `e` with `@T::Target::Output **e`
 /// and repeat this until types match and raise an error otherwise.
+///
+/// # Safety
+///
+/// `Output` must be a valid pointer type to produce from a `From` by borrowing `**e`.
 pub unsafe trait PlaceCoerce<From>: HasPlace
 where
     From: HasPlace<Target = Self>,
@@ -106,6 +301,10 @@ where
 /// named `field` as well. That field has type `<X as
 /// PlaceWrap<proj_ty!(X::Target.field)>>::WrappedProj::Target`, and `WrappedProj` is the
 /// projection used when we refer to that field.
+///
+/// # Safety
+///
+/// `wrap_proj` must return a projection to the same field through `Self`.
 pub unsafe trait PlaceWrap<P: Projection<Source = Self::Target>>: HasPlace {
     type WrappedProj: Projection<Source = Self>;
     fn wrap_proj(p: &P) -> Self::WrappedProj;
@@ -1,8 +1,13 @@
-use std::{marker::PhantomData, ptr::Pointee};
+use std::{marker::PhantomData, ops::Range, ptr::Pointee};
 
 use crate::*;
 
 // TODO: inspect projections
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a projection",
+    label = "`p!` expects field names to be projection values",
+    note = "declare one with `mk_field_proj!(struct field(Struct.field: Type))`"
+)]
 pub trait Projection {
     type Source: ?Sized;
     type Target: ?Sized;
@@ -14,10 +19,195 @@ pub trait Projection {
     ) -> <Self::Target as Pointee>::Metadata;
 }
 
+/// Marker for projections that are known to be correct: for any valid `Source`, the `Target` at
+/// `offset` is in-bounds and of the right type. Projections built by `mk_field_proj!` and the
+/// ones in this crate implement this.
+///
+/// # Safety
+///
+/// `offset` and `project_metadata` must describe a `Target` inside of any valid `Source` with
+/// that metadata, or panic.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not known to be a correct projection",
+    note = "projections declared with `mk_field_proj!` are; other ones need an `unsafe impl SafeProjection`"
+)]
+pub unsafe trait SafeProjection: Projection + ByteProjection {}
+
+/// Projections to a whole place at `offset`, that pointers can access as bytes: all of them
+/// except bit-field projections, whose target is only some bits of an integer there. It is an
+/// auto trait so that projections don't have to opt in; bit-field projections hold a
+/// `BitFieldMarker` to opt out, and so does anything built from them.
+///
+/// # Safety
+///
+/// The target must be a `Target` at `offset`, if the projection is correct.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` projects to a bit field, which isn't a place",
+    label = "only bit-field pointers like `BitMut` can access bit fields"
+)]
+pub unsafe auto trait ByteProjection {}
+
+/// Held by bit-field projections so that they don't implement `ByteProjection`.
+#[doc(hidden)]
+#[derive(Clone, Copy, Default)]
+pub struct BitFieldMarker;
+impl !ByteProjection for BitFieldMarker {}
+// These only use their type parameters in `PhantomData` and metadata, which say nothing about
+// bit fields.
+unsafe impl<T: ?Sized> ByteProjection for NoopProj<T> {}
+unsafe impl<S: ?Sized, T> ByteProjection for SizedProj<S, T> {}
+unsafe impl<S: ?Sized, T: ?Sized> ByteProjection for ErasedProj<S, T> {}
+unsafe impl<S: ?Sized> ByteProjection for IndexProj<S> {}
+unsafe impl<S: ?Sized> ByteProjection for RangeProj<S> {}
+
+/// Projections whose target is aligned whenever the source is. Fields of `#[repr(packed)]`
+/// structs generally aren't, so operations that create references or do aligned reads from safe
+/// code require this.
+///
+/// # Safety
+///
+/// The target must be aligned for `Target` whenever the source is aligned for `Source`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` may project to a misaligned place",
+    label = "this requires `AlignedProjection`",
+    note = "fields of `#[repr(packed)]` structs can be accessed through `UnalignedPtr`"
+)]
+pub unsafe trait AlignedProjection: Projection + ByteProjection {}
+
+/// Projections to places that may be read. Pointers to device memory like `VolatilePtr` require
+/// it; fields declared `WriteOnly<T>` don't implement it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` projects to a write-only place",
+    label = "reading requires `ReadableProjection`"
+)]
+pub trait ReadableProjection: Projection {}
+
+/// Projections to places that may be written. Fields declared `ReadOnly<T>` don't implement it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` projects to a read-only place",
+    label = "writing requires `WritableProjection`"
+)]
+pub trait WritableProjection: Projection {}
+
+/// Projections to a bit field: the target is stored in bits `bit_range()` of the `Storage` integer
+/// at `offset`. There is no place of type `Target` at that offset, so these are never
+/// `SafeProjection` or `ByteProjection`, and only pointers that support them explicitly, like
+/// `BitRef` and `BitMut`, can read and write through them.
+///
+/// Built by `#[derive(FieldProjections)]` for `#[bits(a..b)]` fields.
+///
+/// # Safety
+///
+/// `offset` must point to a `Storage` inside of `Source`, and `bit_range()` must fit in it and
+/// have room for `Target::BITS` bits.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a bit-field projection",
+    label = "bit-field pointers can only read and write `#[bits(..)]` fields"
+)]
+pub unsafe trait BitProjection: Projection<Target: BitValue> {
+    type Storage: BitStorage;
+    fn bit_range(&self) -> Range<u32>;
+}
+
+/// Projections to a whole field of `Source`, identified by its position among the fields in
+/// declaration order. The field holds a `Target`, possibly in a `#[repr(transparent)]` wrapper like
+/// `ReadOnly<Target>`. This lets pointers that don't store structs contiguously, like `SoaRef`,
+/// find the field without using `offset`.
+///
+/// Built by `#[derive(FieldProjections)]` for fields that aren't bit fields.
+///
+/// # Safety
+///
+/// Field `INDEX` of `Source` must be a `Target`, or a `#[repr(transparent)]` wrapper of one.
+pub unsafe trait FieldIndex: Projection<Source: Sized, Target: Sized> {
+    const INDEX: usize;
+}
+
+/// Projections that start with a `FieldIndex` projection: field `FIELD` of `Source`, followed by
+/// a projection inside of it. `p!` builds these from places like `(*r).field.rest`.
+///
+/// # Safety
+///
+/// Field `FIELD` of `Source` must be a `Field`, and `project_field` must return a pointer into
+/// the field it is given.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` doesn't start with a field projection",
+    label = "this pointer can only project to fields and places inside of them"
+)]
+pub unsafe trait FieldPath: Projection<Source: Sized> {
+    /// Position of the first field.
+    const FIELD: usize;
+    /// Type of the first field.
+    type Field: Sized;
+    /// The target, given a pointer to the first field.
+    fn project_field(&self, field: *mut Self::Field) -> *mut Self::Target;
+}
+
+/// Projections that can name the place they project to, as a path of fields and indices like
+/// `a.b[3]`, e.g. to report which parts of a value changed.
+///
+/// Built by `#[derive(FieldProjections)]` and `mk_field_proj!`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't describe its target as a path",
+    label = "this requires `NamedProjection`"
+)]
+pub trait NamedProjection: Projection {
+    /// Append the path from `Source` to `Target`.
+    fn push_path(&self, path: &mut PlacePath);
+}
+
+/// A step in a `PlacePath`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum PathSegment {
+    Field(&'static str),
+    Index(usize),
+}
+
+/// The path from a value to a place inside of it, displayed like `a.b[3]`. The empty path is the
+/// whole value.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct PlacePath(pub Vec<PathSegment>);
+
+impl PlacePath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The path of the target of `p`.
+    pub fn of<P: NamedProjection + ?Sized>(p: &P) -> Self {
+        let mut path = Self::new();
+        p.push_path(&mut path);
+        path
+    }
+    pub fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+    /// Whether this path is `prefix` or a place inside of it.
+    pub fn starts_with(&self, prefix: &PlacePath) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+}
+
+impl std::fmt::Display for PlacePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => write!(f, "{name}")?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(idx) => write!(f, "[{idx}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Extension trait so that `Projection` stays dyn-compatible.
 impl<P: Projection + ?Sized> ProjectionExt for P {}
 pub trait ProjectionExt: Projection {
     /// Convenience method that simply calls the corresponding PlaceBorrow method.
+    ///
+    /// # Safety
+    ///
+    /// See `PlaceBorrow`.
     unsafe fn borrow<'a, X, Y>(&self, ptr: *const X) -> Y
     where
         X: HasPlace<Target = Self::Source>,
@@ -27,6 +217,10 @@ pub trait ProjectionExt: Projection {
         unsafe { PlaceBorrow::borrow(ptr, self) }
     }
     /// Convenience method that simply calls the corresponding PlaceRead method.
+    ///
+    /// # Safety
+    ///
+    /// See `PlaceRead`.
     unsafe fn read<X>(&self, ptr: *const X) -> Self::Target
     where
         X: PlaceRead<Self>,
@@ -34,7 +228,11 @@ pub trait ProjectionExt: Projection {
     {
         unsafe { PlaceRead::read(ptr, self) }
     }
-    /// Convenience method that simply calls the corresponding PlaceRead method.
+    /// Convenience method that simply calls the corresponding PlaceWrite method.
+    ///
+    /// # Safety
+    ///
+    /// See `PlaceWrite`.
     unsafe fn write<X>(&self, ptr: *mut X, val: Self::Target)
     where
         X: PlaceWrite<Self>,
@@ -43,6 +241,10 @@ pub trait ProjectionExt: Projection {
         unsafe { PlaceWrite::write(ptr, self, val) }
     }
     /// Convenience method that simply calls the corresponding PlaceDeref method.
+    ///
+    /// # Safety
+    ///
+    /// See `PlaceDeref`.
     unsafe fn deref<X>(&self, ptr: *mut X) -> *const Self::Target
     where
         X: HasPlace<Target = Self::Source>,
@@ -51,6 +253,66 @@ pub trait ProjectionExt: Projection {
     {
         unsafe { PlaceDeref::double_deref(ptr, self) }
     }
+    /// Convenience method that simply calls the corresponding PlaceIndex method.
+    ///
+    /// # Safety
+    ///
+    /// See `PlaceIndex`.
+    unsafe fn index<X, I>(&self, ptr: *const X, idx: I) -> X::Output
+    where
+        X: HasPlace<Target = Self::Source>,
+        X: PlaceIndex<Self, I>,
+    {
+        unsafe { PlaceIndex::index(ptr, self, idx) }
+    }
+
+    /// Safe version of `borrow`, for projections and pointers known to be sound.
+    fn safe_borrow<'a, X, Y>(&self, ptr: &X) -> Y
+    where
+        Self: SafeProjection,
+        X: HasPlace<Target = Self::Source>,
+        Y: HasPlace<Target = Self::Target>,
+        X: SafePlaceBorrow<'a, Self, Y>,
+    {
+        unsafe { PlaceBorrow::borrow(ptr, self) }
+    }
+    /// Safe version of `read`, for projections and pointers known to be sound.
+    fn safe_read<X>(&self, ptr: &X) -> Self::Target
+    where
+        Self: SafeProjection,
+        X: SafePlaceRead<Self>,
+        Self::Target: Sized,
+    {
+        unsafe { PlaceRead::read(ptr, self) }
+    }
+    /// Safe version of `write`, for projections and pointers known to be sound.
+    fn safe_write<X>(&self, ptr: &X, val: Self::Target)
+    where
+        Self: SafeProjection,
+        X: SafePlaceWrite<Self>,
+        Self::Target: Sized,
+    {
+        unsafe { PlaceWrite::write(std::ptr::from_ref(ptr).cast_mut(), self, val) }
+    }
+    /// Safe version of `deref`, for projections and pointers known to be sound.
+    fn safe_deref<'a, X>(&self, ptr: &'a X) -> &'a Self::Target
+    where
+        Self: SafeProjection,
+        X: HasPlace<Target = Self::Source>,
+        X: SafePlaceDeref<Self>,
+        Self::Target: HasPlace,
+    {
+        unsafe { &*PlaceDeref::double_deref(std::ptr::from_ref(ptr).cast_mut(), self) }
+    }
+    /// Safe version of `index`, for projections and pointers known to be sound.
+    fn safe_index<X, I>(&self, ptr: &X, idx: I) -> X::Output
+    where
+        Self: SafeProjection,
+        X: HasPlace<Target = Self::Source>,
+        X: SafePlaceIndex<Self, I>,
+    {
+        unsafe { PlaceIndex::index(ptr, self, idx) }
+    }
 
     /// When the target is sized, we know a projection is just an offset so we can make it sized
     /// even if we had a `dyn Projection`.
@@ -60,7 +322,7 @@ pub trait ProjectionExt: Projection {
         Self::Source: Sized,
         Self::Target: Sized,
     {
-        SizedProj(self.offset(()), PhantomData, PhantomData)
+        SizedProj::new(self.offset(()))
     }
 
     fn compose<Q>(self, other: Q) -> ComposeProj<Self, Q>
@@ -73,16 +335,23 @@ pub trait ProjectionExt: Projection {
 }
 
 pub struct NoopProj<T: ?Sized>(PhantomData<T>);
-impl<T> Default for NoopProj<T> {
+impl<T: ?Sized> Default for NoopProj<T> {
     fn default() -> Self {
         Self(Default::default())
     }
 }
-impl<T> Clone for NoopProj<T> {
+impl<T: ?Sized> Clone for NoopProj<T> {
     fn clone(&self) -> Self {
-        Self(self.0.clone())
+        Self(self.0)
     }
 }
+unsafe impl<T: ?Sized> SafeProjection for NoopProj<T> {}
+unsafe impl<T: ?Sized> AlignedProjection for NoopProj<T> {}
+impl<T: ?Sized> ReadableProjection for NoopProj<T> {}
+impl<T: ?Sized> WritableProjection for NoopProj<T> {}
+impl<T: ?Sized> NamedProjection for NoopProj<T> {
+    fn push_path(&self, _: &mut PlacePath) {}
+}
 impl<T: ?Sized> Projection for NoopProj<T> {
     type Source = T;
     type Target = T;
@@ -97,9 +366,29 @@ impl<T: ?Sized> Projection for NoopProj<T> {
     }
 }
 
-/// Sized projection that holds only an offset.
+/// Sized projection that holds only an offset. Unlike other projections, it can be built and
+/// composed in const contexts.
 #[derive(Clone)]
 pub struct SizedProj<S: ?Sized, T>(usize, PhantomData<S>, PhantomData<T>);
+impl<S, T> SizedProj<S, T> {
+    /// Make a projection from a raw offset, e.g. from `offset_of!`.
+    pub const fn new(offset: usize) -> Self {
+        SizedProj(offset, PhantomData, PhantomData)
+    }
+    /// Const version of `Projection::offset`.
+    pub const fn byte_offset(&self) -> usize {
+        self.0
+    }
+    /// Const version of `ProjectionExt::as_sized`.
+    pub const fn as_sized(&self) -> SizedProj<S, T> {
+        SizedProj::new(self.0)
+    }
+    /// Const version of `ProjectionExt::compose`. It has another name so that `p!`, which calls
+    /// `compose`, can use `SizedProj`s like other projections.
+    pub const fn then<U>(self, other: SizedProj<T, U>) -> SizedProj<S, U> {
+        SizedProj::new(self.0 + other.0)
+    }
+}
 impl<S, T> Projection for SizedProj<S, T> {
     type Source = S;
     type Target = T;
@@ -113,12 +402,125 @@ impl<S, T> Projection for SizedProj<S, T> {
     }
 }
 
+/// A projection already evaluated for one source metadata: just an offset and the target
+/// metadata. It is `Copy`, has the same type whatever projection it came from, and doesn't
+/// allocate, so custom pointers can store it instead of a `Box<dyn Projection>`.
+///
+/// Its `Projection` impl panics if used with a different source metadata.
+pub struct ErasedProj<S: ?Sized, T: ?Sized> {
+    offset: usize,
+    source_meta: <S as Pointee>::Metadata,
+    meta: <T as Pointee>::Metadata,
+}
+impl<S: ?Sized, T: ?Sized> Clone for ErasedProj<S, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<S: ?Sized, T: ?Sized> Copy for ErasedProj<S, T> {}
+impl<S: ?Sized> ErasedProj<S, S> {
+    /// The identity projection on a source with metadata `meta`.
+    pub fn identity(meta: <S as Pointee>::Metadata) -> Self {
+        Self {
+            offset: 0,
+            source_meta: meta,
+            meta,
+        }
+    }
+}
+impl<S: ?Sized, T: ?Sized> ErasedProj<S, T> {
+    /// Evaluate `p` on a source with metadata `meta`.
+    pub fn new<P>(p: &P, meta: <S as Pointee>::Metadata) -> Self
+    where
+        P: Projection<Source = S, Target = T> + ByteProjection + ?Sized,
+    {
+        Self {
+            offset: p.offset(meta),
+            source_meta: meta,
+            meta: p.project_metadata(meta),
+        }
+    }
+    /// This projection followed by `p`.
+    pub fn then<P>(&self, p: &P) -> ErasedProj<S, P::Target>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        ErasedProj {
+            offset: self.offset + p.offset(self.meta),
+            source_meta: self.source_meta,
+            meta: p.project_metadata(self.meta),
+        }
+    }
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+    /// Metadata of the target.
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.meta
+    }
+}
+impl<S: ?Sized, T: ?Sized> Projection for ErasedProj<S, T> {
+    type Source = S;
+    type Target = T;
+    fn offset(&self, meta: <Self::Source as Pointee>::Metadata) -> usize {
+        assert_eq!(
+            meta, self.source_meta,
+            "projection used on the wrong source"
+        );
+        self.offset
+    }
+    fn project_metadata(
+        &self,
+        meta: <Self::Source as Pointee>::Metadata,
+    ) -> <Self::Target as Pointee>::Metadata {
+        assert_eq!(
+            meta, self.source_meta,
+            "projection used on the wrong source"
+        );
+        self.meta
+    }
+}
+
 /// Projection `P` followed by `Q`. `P` may be unsized.
 #[derive(Clone)]
 pub struct ComposeProj<P: ?Sized, Q> {
     q: Q,
     p: P,
 }
+unsafe impl<P, Q> SafeProjection for ComposeProj<P, Q>
+where
+    P: SafeProjection + ?Sized,
+    Q: SafeProjection<Source = P::Target>,
+{
+}
+unsafe impl<P, Q> AlignedProjection for ComposeProj<P, Q>
+where
+    P: AlignedProjection + ?Sized,
+    Q: AlignedProjection<Source = P::Target>,
+{
+}
+impl<P, Q> ReadableProjection for ComposeProj<P, Q>
+where
+    P: ReadableProjection + ?Sized,
+    Q: ReadableProjection<Source = P::Target>,
+{
+}
+impl<P, Q> WritableProjection for ComposeProj<P, Q>
+where
+    P: WritableProjection + ?Sized,
+    Q: WritableProjection<Source = P::Target>,
+{
+}
+impl<P, Q> NamedProjection for ComposeProj<P, Q>
+where
+    P: NamedProjection + ?Sized,
+    Q: NamedProjection<Source = P::Target>,
+{
+    fn push_path(&self, path: &mut PlacePath) {
+        self.p.push_path(path);
+        self.q.push_path(path);
+    }
+}
 impl<P, Q> Projection for ComposeProj<P, Q>
 where
     P: Projection + ?Sized,
@@ -137,3 +539,208 @@ where
         self.q.project_metadata(self.p.project_metadata(meta))
     }
 }
+
+// `p!` composes projections as `a.compose(b.compose(NoopProj))`, so the bit field is either the
+// last projection or the one before the final `NoopProj`.
+unsafe impl<P, Q> BitProjection for ComposeProj<P, Q>
+where
+    P: Projection + ?Sized,
+    Q: BitProjection<Source = P::Target>,
+{
+    type Storage = Q::Storage;
+    fn bit_range(&self) -> Range<u32> {
+        self.q.bit_range()
+    }
+}
+unsafe impl<P> BitProjection for ComposeProj<P, NoopProj<P::Target>>
+where
+    P: BitProjection + ?Sized,
+{
+    type Storage = P::Storage;
+    fn bit_range(&self) -> Range<u32> {
+        self.p.bit_range()
+    }
+}
+
+unsafe impl<P, Q> FieldPath for ComposeProj<P, Q>
+where
+    P: FieldIndex,
+    Q: Projection<Source = P::Target> + ByteProjection,
+{
+    const FIELD: usize = P::INDEX;
+    type Field = P::Target;
+    fn project_field(&self, field: *mut P::Target) -> *mut Q::Target {
+        unsafe { self.q.borrow::<*mut _, *mut _>(&raw const field) }
+    }
+}
+
+/// A place that can be indexed in-place, like `[T]` or `[T; N]`. This is the place-level
+/// counterpart of `Index`: it produces the projection to the indexed subplace, which pointers can
+/// then use in their `PlaceIndex` impls.
+pub trait IndexProjection<I> {
+    type Output: ?Sized;
+    type Proj: Projection<Source = Self, Target = Self::Output> + ByteProjection;
+    fn index_proj(idx: I) -> Self::Proj;
+}
+
+/// Projection to the element at a given index of a slice or array. Panics if the index is out of
+/// bounds.
+pub struct IndexProj<S: ?Sized>(usize, PhantomData<S>);
+impl<S: ?Sized> IndexProj<S> {
+    pub fn new(idx: usize) -> Self {
+        Self(idx, PhantomData)
+    }
+}
+impl<S: ?Sized> Clone for IndexProj<S> {
+    fn clone(&self) -> Self {
+        Self(self.0, PhantomData)
+    }
+}
+unsafe impl<T> SafeProjection for IndexProj<[T]> {}
+unsafe impl<T, const N: usize> SafeProjection for IndexProj<[T; N]> {}
+unsafe impl<T> AlignedProjection for IndexProj<[T]> {}
+unsafe impl<T, const N: usize> AlignedProjection for IndexProj<[T; N]> {}
+impl<S: ?Sized> ReadableProjection for IndexProj<S> where Self: Projection {}
+impl<S: ?Sized> WritableProjection for IndexProj<S> where Self: Projection {}
+impl<S: ?Sized> NamedProjection for IndexProj<S>
+where
+    Self: Projection,
+{
+    fn push_path(&self, path: &mut PlacePath) {
+        path.push(PathSegment::Index(self.0));
+    }
+}
+impl<T> Projection for IndexProj<[T]> {
+    type Source = [T];
+    type Target = T;
+    fn offset(&self, len: usize) -> usize {
+        assert!(self.0 < len, "index out of bounds");
+        self.0 * size_of::<T>()
+    }
+    fn project_metadata(&self, _: usize) -> <Self::Target as Pointee>::Metadata {}
+}
+impl<T, const N: usize> Projection for IndexProj<[T; N]> {
+    type Source = [T; N];
+    type Target = T;
+    fn offset(&self, _m: <Self::Source as Pointee>::Metadata) -> usize {
+        assert!(self.0 < N, "index out of bounds");
+        self.0 * size_of::<T>()
+    }
+    fn project_metadata(
+        &self,
+        _: <Self::Source as Pointee>::Metadata,
+    ) -> <Self::Target as Pointee>::Metadata {
+    }
+}
+
+/// Projection to a subslice of a slice or array. Panics if the range is out of bounds.
+pub struct RangeProj<S: ?Sized>(Range<usize>, PhantomData<S>);
+impl<S: ?Sized> RangeProj<S> {
+    pub fn new(range: Range<usize>) -> Self {
+        Self(range, PhantomData)
+    }
+    fn check(&self, len: usize) {
+        assert!(
+            self.0.start <= self.0.end && self.0.end <= len,
+            "range out of bounds"
+        );
+    }
+}
+impl<S: ?Sized> Clone for RangeProj<S> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+unsafe impl<T> SafeProjection for RangeProj<[T]> {}
+unsafe impl<T, const N: usize> SafeProjection for RangeProj<[T; N]> {}
+unsafe impl<T> AlignedProjection for RangeProj<[T]> {}
+unsafe impl<T, const N: usize> AlignedProjection for RangeProj<[T; N]> {}
+impl<S: ?Sized> ReadableProjection for RangeProj<S> where Self: Projection {}
+impl<S: ?Sized> WritableProjection for RangeProj<S> where Self: Projection {}
+impl<T> Projection for RangeProj<[T]> {
+    type Source = [T];
+    type Target = [T];
+    fn offset(&self, len: usize) -> usize {
+        self.check(len);
+        self.0.start * size_of::<T>()
+    }
+    fn project_metadata(&self, _: usize) -> usize {
+        self.0.end - self.0.start
+    }
+}
+impl<T, const N: usize> Projection for RangeProj<[T; N]> {
+    type Source = [T; N];
+    type Target = [T];
+    fn offset(&self, _: ()) -> usize {
+        self.check(N);
+        self.0.start * size_of::<T>()
+    }
+    fn project_metadata(&self, _: ()) -> usize {
+        self.0.end - self.0.start
+    }
+}
+
+/// Projection to a substring. Panics if the range is out of bounds. Char boundaries can't be
+/// checked without the string contents, so this is not a `SafeProjection`.
+pub struct StrRangeProj(Range<usize>);
+impl StrRangeProj {
+    pub fn new(range: Range<usize>) -> Self {
+        Self(range)
+    }
+}
+unsafe impl AlignedProjection for StrRangeProj {}
+impl Clone for StrRangeProj {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+impl Projection for StrRangeProj {
+    type Source = str;
+    type Target = str;
+    fn offset(&self, len: usize) -> usize {
+        assert!(
+            self.0.start <= self.0.end && self.0.end <= len,
+            "range out of bounds"
+        );
+        self.0.start
+    }
+    fn project_metadata(&self, _: usize) -> usize {
+        self.0.end - self.0.start
+    }
+}
+
+impl<T> IndexProjection<usize> for [T] {
+    type Output = T;
+    type Proj = IndexProj<[T]>;
+    fn index_proj(idx: usize) -> Self::Proj {
+        IndexProj::new(idx)
+    }
+}
+impl<T, const N: usize> IndexProjection<usize> for [T; N] {
+    type Output = T;
+    type Proj = IndexProj<[T; N]>;
+    fn index_proj(idx: usize) -> Self::Proj {
+        IndexProj::new(idx)
+    }
+}
+impl<T> IndexProjection<Range<usize>> for [T] {
+    type Output = [T];
+    type Proj = RangeProj<[T]>;
+    fn index_proj(range: Range<usize>) -> Self::Proj {
+        RangeProj::new(range)
+    }
+}
+impl<T, const N: usize> IndexProjection<Range<usize>> for [T; N] {
+    type Output = [T];
+    type Proj = RangeProj<[T; N]>;
+    fn index_proj(range: Range<usize>) -> Self::Proj {
+        RangeProj::new(range)
+    }
+}
+impl IndexProjection<Range<usize>> for str {
+    type Output = str;
+    type Proj = StrRangeProj;
+    fn index_proj(range: Range<usize>) -> Self::Proj {
+        StrRangeProj::new(range)
+    }
+}
@@ -0,0 +1,181 @@
+use std::{marker::PhantomData, num::NonZero};
+
+use crate::*;
+
+/// A pointer stored as an offset from its own address, so that data structures linked with
+/// `RelPtr`s stay valid when the whole memory region moves, e.g. into shared memory mapped at
+/// different addresses.
+///
+/// `p!((*(*node).next).value)` follows the pointer in `node.next` with `PlaceDeref`. Reads and
+/// writes through a `RelPtr` go through `RelValue`: a `RelPtr` read out of memory is detached,
+/// i.e. it holds the address of its target, and `p!((*(*node).next).next = RelPtr::to(target))`
+/// stores the offset from where it is written. Only use detached `RelPtr`s as values for such
+/// writes; writing one with other pointers or calling `get` on it gives garbage.
+#[repr(transparent)]
+pub struct RelPtr<T> {
+    /// The offset of the target from this `RelPtr`, or its address if detached, with the top
+    /// bit flipped so that a pointer to itself isn't null. The offset `isize::MIN` can't happen
+    /// between two places in the same address space.
+    offset: Option<NonZero<isize>>,
+    phantom: PhantomData<*const T>,
+}
+
+impl<T> RelPtr<T> {
+    pub const fn null() -> Self {
+        Self {
+            offset: None,
+            phantom: PhantomData,
+        }
+    }
+    /// A detached pointer to `target`, to be written with `p!` through another `RelPtr`.
+    pub fn to(target: *const T) -> Self {
+        Self::with_offset(target, 0)
+    }
+    pub fn is_null(&self) -> bool {
+        self.offset.is_none()
+    }
+    /// The target of this pointer.
+    pub fn get(&self) -> *const T {
+        unsafe { Self::resolve(self) }
+    }
+    /// Point to `target`, or make this null if it is null. The offset is taken from where this
+    /// `RelPtr` is now, so it must not move on its own afterwards.
+    pub fn set(&mut self, target: *const T) {
+        *self = Self::with_offset(target, std::ptr::from_mut(self).addr());
+    }
+
+    /// A pointer at address `from` to `target`.
+    fn with_offset(target: *const T, from: usize) -> Self {
+        let offset = (!target.is_null()).then(|| {
+            let offset = target.addr().wrapping_sub(from) as isize;
+            NonZero::new(offset ^ isize::MIN).expect("`RelPtr` target is out of reach")
+        });
+        Self {
+            offset,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The target of the pointer at `ptr`.
+    unsafe fn resolve(ptr: *const Self) -> *mut T {
+        match unsafe { (*ptr).offset } {
+            None => std::ptr::null_mut(),
+            Some(offset) => ptr
+                .cast::<u8>()
+                .wrapping_byte_offset(offset.get() ^ isize::MIN)
+                .cast_mut()
+                .cast(),
+        }
+    }
+    /// The target of the pointer at `ptr`, which must not be null.
+    unsafe fn project<P>(ptr: *const Self, p: &P) -> *mut P::Target
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        let target = unsafe { Self::resolve(ptr) };
+        assert!(!target.is_null(), "dereferenced a null `RelPtr`");
+        unsafe { p.borrow::<*mut _, *mut _>(&raw const target) }
+    }
+}
+
+impl<T> Default for RelPtr<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+/// Values that can be read and written through a `RelPtr`. `Copy` values are copied, and
+/// `RelPtr`s are converted between their relative form in memory and their detached form as
+/// values.
+///
+/// # Safety
+///
+/// `load` must return the value that the last `store` to the same address stored.
+pub unsafe trait RelValue: Sized {
+    /// # Safety
+    ///
+    /// `src` must be valid for reads of an initialized `Self`.
+    unsafe fn load(src: *const Self) -> Self;
+    /// # Safety
+    ///
+    /// `dst` must be valid for writes of a `Self`, and stay where it is until it is loaded.
+    unsafe fn store(dst: *mut Self, x: Self);
+}
+unsafe impl<T: Copy> RelValue for T {
+    unsafe fn load(src: *const Self) -> Self {
+        unsafe { src.read() }
+    }
+    unsafe fn store(dst: *mut Self, x: Self) {
+        unsafe { dst.write(x) }
+    }
+}
+unsafe impl<T> RelValue for RelPtr<T> {
+    unsafe fn load(src: *const Self) -> Self {
+        Self::to(unsafe { Self::resolve(src) })
+    }
+    unsafe fn store(dst: *mut Self, x: Self) {
+        // `x` is detached, so its offset is the address of the target.
+        let target = x.offset.map_or(std::ptr::null(), |offset| {
+            std::ptr::without_provenance((offset.get() ^ isize::MIN) as usize)
+        });
+        unsafe { dst.write(Self::with_offset(target, dst.addr())) }
+    }
+}
+
+impl<T> HasPlace for RelPtr<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, *const P::Target> for RelPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> *const P::Target {
+        unsafe { Self::project(ptr, p) }
+    }
+}
+unsafe impl<'a, P> PlaceBorrow<'a, P, *mut P::Target> for RelPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> *mut P::Target {
+        unsafe { Self::project(ptr, p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for RelPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: RelValue,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target {
+        unsafe { RelValue::load(Self::project(ptr, p)) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for RelPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: RelValue,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        unsafe { RelValue::store(Self::project(ptr, p), x) }
+    }
+}
+
+unsafe impl<P> PlaceDeref<P> for RelPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: HasPlace,
+{
+    unsafe fn double_deref(ptr: *mut Self, p: &P) -> *const P::Target {
+        unsafe { Self::project(ptr, p) }
+    }
+}
@@ -0,0 +1,215 @@
+use std::{cell::RefCell, collections::HashMap, io, ptr::Pointee, rc::Rc};
+
+use crate::*;
+
+/// Somewhere bytes live outside of process memory, addressed by a byte offset.
+pub trait ByteStore {
+    /// Fill `buf` with the bytes at `addr..addr + buf.len()`.
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()>;
+    /// Overwrite the bytes at `addr..addr + bytes.len()`.
+    fn write(&self, addr: u64, bytes: &[u8]) -> io::Result<()>;
+}
+
+/// Sparse in-memory store: a map from page number to page. Missing pages read as zeros.
+#[derive(Default)]
+pub struct MemStore {
+    pages: RefCell<HashMap<u64, Box<[u8; MemStore::PAGE_SIZE]>>>,
+}
+impl MemStore {
+    const PAGE_SIZE: usize = 4096;
+
+    /// Split `addr..addr + len` into `(page, offset in page, offset in buffer, len)` chunks.
+    fn chunks(addr: u64, len: usize) -> impl Iterator<Item = (u64, usize, usize, usize)> {
+        let page_size = Self::PAGE_SIZE as u64;
+        let mut done = 0;
+        std::iter::from_fn(move || {
+            if done == len {
+                return None;
+            }
+            let a = addr + done as u64;
+            let start = (a % page_size) as usize;
+            let n = (len - done).min(Self::PAGE_SIZE - start);
+            let chunk = (a / page_size, start, done, n);
+            done += n;
+            Some(chunk)
+        })
+    }
+}
+impl ByteStore for MemStore {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        let pages = self.pages.borrow();
+        for (page, start, done, n) in Self::chunks(addr, buf.len()) {
+            let out = &mut buf[done..done + n];
+            match pages.get(&page) {
+                Some(page) => out.copy_from_slice(&page[start..start + n]),
+                None => out.fill(0),
+            }
+        }
+        Ok(())
+    }
+    fn write(&self, addr: u64, bytes: &[u8]) -> io::Result<()> {
+        let mut pages = self.pages.borrow_mut();
+        for (page, start, done, n) in Self::chunks(addr, bytes.len()) {
+            let page = pages
+                .entry(page)
+                .or_insert_with(|| Box::new([0; Self::PAGE_SIZE]));
+            page[start..start + n].copy_from_slice(&bytes[done..done + n]);
+        }
+        Ok(())
+    }
+}
+
+/// A file used as a byte store. Addresses are file offsets.
+#[cfg(unix)]
+pub struct FileStore(pub std::fs::File);
+#[cfg(unix)]
+impl ByteStore for FileStore {
+    fn read(&self, addr: u64, buf: &mut [u8]) -> io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(&self.0, buf, addr)
+    }
+    fn write(&self, addr: u64, bytes: &[u8]) -> io::Result<()> {
+        std::os::unix::fs::FileExt::write_all_at(&self.0, bytes, addr)
+    }
+}
+
+/// A pointer to a `T` that lives in a `ByteStore`. Projecting only computes addresses; reading
+/// fetches just the bytes of the projected place and writing stores just those.
+///
+/// Place operations can't return errors, so they panic if the store fails.
+pub struct RemotePtr<T: ?Sized> {
+    store: Rc<dyn ByteStore>,
+    addr: u64,
+    meta: <T as Pointee>::Metadata,
+}
+
+impl<T> RemotePtr<T> {
+    pub fn new(store: Rc<dyn ByteStore>, addr: u64) -> Self {
+        Self::from_raw_parts(store, addr, ())
+    }
+}
+impl<T: ?Sized> RemotePtr<T> {
+    pub fn from_raw_parts(
+        store: Rc<dyn ByteStore>,
+        addr: u64,
+        meta: <T as Pointee>::Metadata,
+    ) -> Self {
+        Self { store, addr, meta }
+    }
+    pub fn addr(&self) -> u64 {
+        self.addr
+    }
+    pub fn metadata(&self) -> <T as Pointee>::Metadata {
+        self.meta
+    }
+
+    /// Address and metadata of the subplace `p`.
+    fn project<P>(&self, p: &P) -> RemotePtr<P::Target>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        RemotePtr {
+            store: self.store.clone(),
+            addr: self.addr + p.offset(self.meta) as u64,
+            meta: p.project_metadata(self.meta),
+        }
+    }
+}
+impl<T> RemotePtr<T> {
+    /// Fetch the `T`.
+    ///
+    /// # Safety
+    ///
+    /// The stored bytes must be a valid `T`.
+    pub unsafe fn try_read(&self) -> io::Result<T> {
+        let mut buf = vec![0u8; size_of::<T>()];
+        self.store.read(self.addr, &mut buf)?;
+        Ok(unsafe { buf.as_ptr().cast::<T>().read_unaligned() })
+    }
+}
+impl<T: NoUninit> RemotePtr<T> {
+    /// Store `x`. `T: NoUninit` because all its bytes are sent to the store.
+    pub fn try_write(&self, x: T) -> io::Result<()> {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(std::ptr::from_ref(&x).cast::<u8>(), size_of::<T>())
+        };
+        self.store.write(self.addr, bytes)
+    }
+}
+
+impl<T, const N: usize> RemotePtr<[T; N]> {
+    /// Forget the length of the array, like the `[T; N]` to `[T]` unsizing coercion.
+    pub fn unsize(self) -> RemotePtr<[T]> {
+        RemotePtr::from_raw_parts(self.store, self.addr, N)
+    }
+}
+
+impl<T: ?Sized> Clone for RemotePtr<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            addr: self.addr,
+            meta: self.meta,
+        }
+    }
+}
+
+impl<T: ?Sized> HasPlace for RemotePtr<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, RemotePtr<P::Target>> for RemotePtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> RemotePtr<P::Target> {
+        unsafe { (*ptr).project(p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for RemotePtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).try_read() }.expect("remote read failed")
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for RemotePtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: NoUninit,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        unsafe { (*ptr).project(p).try_write(x) }.expect("remote write failed")
+    }
+}
+
+unsafe impl<P, I> PlaceIndex<P, I> for RemotePtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = RemotePtr<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        unsafe { (*ptr).project(p).project(&q) }
+    }
+}
+
+// Borrowing and indexing only compute addresses, so they're fine for any correct projection.
+unsafe impl<'a, P> SafePlaceBorrow<'a, P, RemotePtr<P::Target>> for RemotePtr<P::Source> where
+    P: SafeProjection + ?Sized
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for RemotePtr<P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
@@ -0,0 +1,278 @@
+use std::{fmt, marker::PhantomData, ptr::NonNull};
+
+use crate::*;
+
+/// Structs that can be stored as a struct of arrays, with one column per field. Derive it with
+/// `#[derive(Soa)]`.
+///
+/// # Safety
+///
+/// `column(columns, i)` and `column_mut(columns, i)` must point to the first element of a
+/// column holding field `i` of all the elements, in order, for the fields that `FieldIndex`
+/// knows about. The pointer from `column_mut` must be valid for writes.
+pub unsafe trait Soa: Sized {
+    type Columns: Default;
+    fn push(columns: &mut Self::Columns, x: Self);
+    fn swap_remove(columns: &mut Self::Columns, index: usize) -> Self;
+    /// The start of the column of field `field`, for reads.
+    fn column(columns: &Self::Columns, field: usize) -> *const u8;
+    /// The start of the column of field `field`, for reads and writes.
+    fn column_mut(columns: &mut Self::Columns, field: usize) -> *mut u8;
+}
+
+/// A `Vec<T>` stored as one `Vec` per field of `T`, so that a loop over one field only touches
+/// that field's memory.
+///
+/// Elements are accessed through `SoaRef` and `SoaMut`, which project to fields by their
+/// `FieldIndex` instead of their offset in `T`: `p!(@_ (*r).pos)` is a reference into the `pos`
+/// column.
+pub struct SoaVec<T: Soa> {
+    columns: T::Columns,
+    len: usize,
+}
+
+impl<T: Soa> Default for SoaVec<T> {
+    fn default() -> Self {
+        Self {
+            columns: T::Columns::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Soa> SoaVec<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn push(&mut self, x: T) {
+        T::push(&mut self.columns, x);
+        self.len += 1;
+    }
+    /// Remove element `index`, replacing it with the last one. Panics if out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "index out of bounds");
+        self.len -= 1;
+        T::swap_remove(&mut self.columns, index)
+    }
+    pub fn get(&self, index: usize) -> Option<SoaRef<'_, T>> {
+        (index < self.len).then_some(SoaRef {
+            columns: &self.columns,
+            index,
+        })
+    }
+    pub fn get_mut(&mut self, index: usize) -> Option<SoaMut<'_, T>> {
+        (index < self.len).then_some(SoaMut {
+            columns: NonNull::from(&mut self.columns),
+            index,
+            phantom: PhantomData,
+        })
+    }
+    /// The whole column of a field, e.g. `v.column(pos)`.
+    pub fn column<F>(&self, _: F) -> &[F::Target]
+    where
+        F: FieldIndex<Source = T> + ReadableProjection,
+    {
+        let start = T::column(&self.columns, F::INDEX).cast();
+        unsafe { std::slice::from_raw_parts(start, self.len) }
+    }
+    /// The whole column of a field, mutably.
+    pub fn column_mut<F>(&mut self, _: F) -> &mut [F::Target]
+    where
+        F: FieldIndex<Source = T> + WritableProjection,
+    {
+        let start = T::column_mut(&mut self.columns, F::INDEX).cast();
+        unsafe { std::slice::from_raw_parts_mut(start, self.len) }
+    }
+}
+
+impl<T: Soa> FromIterator<T> for SoaVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        iter.into_iter().for_each(|x| v.push(x));
+        v
+    }
+}
+
+impl<T: Soa> fmt::Debug for SoaVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoaVec").field("len", &self.len).finish()
+    }
+}
+
+/// Pointer to the field `P::FIELD` of element `index`, then projected by the rest of `P`, given
+/// the column of that field.
+fn project<P>(column: *mut u8, index: usize, p: &P) -> *mut P::Target
+where
+    P: FieldPath + ?Sized,
+{
+    let field = column.cast::<P::Field>();
+    p.project_field(unsafe { field.add(index) })
+}
+
+/// Shared pointer to an element of a `SoaVec<T>`. Elements aren't stored as a whole, so it can only
+/// be projected to fields and places inside of them.
+pub struct SoaRef<'a, T: Soa> {
+    columns: &'a T::Columns,
+    index: usize,
+}
+
+impl<T: Soa> SoaRef<'_, T> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// Only for reads: the column is from `Soa::column`.
+    fn project<P>(&self, p: &P) -> *mut P::Target
+    where
+        P: FieldPath<Source = T> + ?Sized,
+    {
+        let column = T::column(self.columns, P::FIELD).cast_mut();
+        project(column, self.index, p)
+    }
+}
+impl<T: Soa> Clone for SoaRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: Soa> Copy for SoaRef<'_, T> {}
+
+impl<T: Soa> HasPlace for SoaRef<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, &'a P::Target> for SoaRef<'b, P::Source>
+where
+    P: FieldPath + ?Sized,
+    P::Source: Soa,
+    P::Target: 'a,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a P::Target {
+        unsafe { &*(*ptr).project(p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for SoaRef<'_, P::Source>
+where
+    P: FieldPath + ReadableProjection + ?Sized,
+    P::Source: Soa,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).read() }
+    }
+}
+
+/// Mutable pointer to an element of a `SoaVec<T>`. Like `SoaRef`, it can only be projected to
+/// fields and places inside of them.
+pub struct SoaMut<'a, T: Soa> {
+    columns: NonNull<T::Columns>,
+    index: usize,
+    phantom: PhantomData<&'a mut SoaVec<T>>,
+}
+
+impl<T: Soa> SoaMut<'_, T> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    fn project<P>(&self, p: &P) -> *mut P::Target
+    where
+        P: FieldPath<Source = T> + ?Sized,
+    {
+        // The `&mut` only covers the `Columns`, not the elements that other borrows point to.
+        let column = T::column_mut(unsafe { &mut *self.columns.as_ptr() }, P::FIELD);
+        project(column, self.index, p)
+    }
+}
+
+impl<T: Soa> HasPlace for SoaMut<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, &'a P::Target> for SoaMut<'b, P::Source>
+where
+    P: FieldPath + ?Sized,
+    P::Source: Soa,
+    P::Target: 'a,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a P::Target {
+        unsafe { &*(*ptr).project(p) }
+    }
+}
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, &'a mut P::Target> for SoaMut<'b, P::Source>
+where
+    P: FieldPath + WritableProjection + ?Sized,
+    P::Source: Soa,
+    P::Target: 'a,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Unique;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a mut P::Target {
+        unsafe { &mut *(*ptr).project(p) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for SoaMut<'_, P::Source>
+where
+    P: FieldPath + ReadableProjection + ?Sized,
+    P::Source: Soa,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).read() }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for SoaMut<'_, P::Source>
+where
+    P: FieldPath + WritableProjection + ?Sized,
+    P::Source: Soa,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).write(x) }
+    }
+}
+
+// Column elements are aligned and initialized, so these behave like references to the fields.
+// `p!` doesn't borrow-check, so `SoaMut` can't safely hand out references.
+unsafe impl<'a, 'b: 'a, P> SafePlaceBorrow<'a, P, &'a P::Target> for SoaRef<'b, P::Source>
+where
+    P: FieldPath + SafeProjection + AlignedProjection + ?Sized,
+    P::Source: Soa,
+    P::Target: 'a,
+{
+}
+unsafe impl<P> SafePlaceRead<P> for SoaRef<'_, P::Source>
+where
+    P: FieldPath + SafeProjection + AlignedProjection + ReadableProjection + ?Sized,
+    P::Source: Soa,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceRead<P> for SoaMut<'_, P::Source>
+where
+    P: FieldPath + SafeProjection + AlignedProjection + ReadableProjection + ?Sized,
+    P::Source: Soa,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceWrite<P> for SoaMut<'_, P::Source>
+where
+    P: FieldPath + SafeProjection + AlignedProjection + WritableProjection + ?Sized,
+    P::Source: Soa,
+    P::Target: Copy,
+{
+}
@@ -0,0 +1,205 @@
+use std::{cell::RefCell, collections::BTreeMap, marker::PhantomData, ops::Range, ptr::NonNull};
+
+use crate::*;
+
+/// A write recorded by a `Tracker`: the path of the place and its bytes in the tracked value.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Change {
+    pub path: PlacePath,
+    pub bytes: Range<usize>,
+}
+
+/// The set of places of one value that were written through `Tracked` pointers, e.g. to find out
+/// which parts of a UI state need to be redrawn. Writing the same place again doesn't add a new
+/// change.
+#[derive(Default, Debug)]
+pub struct Tracker {
+    dirty: RefCell<BTreeMap<PlacePath, Range<usize>>>,
+}
+
+impl Tracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Start tracking writes to `x`. Use a separate tracker for each value.
+    pub fn track<'a, T: ?Sized>(&'a self, x: &'a mut T) -> Tracked<'a, T> {
+        let ptr = NonNull::from(x);
+        Tracked {
+            ptr,
+            root: ptr.cast(),
+            path: PlacePath::new(),
+            tracker: self,
+            phantom: PhantomData,
+        }
+    }
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.borrow().is_empty()
+    }
+    /// The written places, ordered by path.
+    pub fn changes(&self) -> Vec<Change> {
+        self.dirty
+            .borrow()
+            .iter()
+            .map(|(path, bytes)| Change {
+                path: path.clone(),
+                bytes: bytes.clone(),
+            })
+            .collect()
+    }
+    /// Whether the place at `path` changed: it, a place inside of it or a place containing it was
+    /// written.
+    pub fn is_changed(&self, path: &PlacePath) -> bool {
+        self.dirty
+            .borrow()
+            .keys()
+            .any(|dirty| dirty.starts_with(path) || path.starts_with(dirty))
+    }
+    /// Whether any written place overlaps `bytes` of the tracked value.
+    pub fn overlaps(&self, bytes: Range<usize>) -> bool {
+        self.dirty
+            .borrow()
+            .values()
+            .any(|dirty| dirty.start < bytes.end && bytes.start < dirty.end)
+    }
+    /// Return the changes and start over.
+    pub fn take(&self) -> Vec<Change> {
+        let changes = self.changes();
+        self.dirty.borrow_mut().clear();
+        changes
+    }
+
+    fn record(&self, path: PlacePath, bytes: Range<usize>) {
+        self.dirty.borrow_mut().insert(path, bytes);
+    }
+}
+
+/// A mutable pointer into a value watched by a `Tracker`. Writes with `p!((*t).a.b = x)` record
+/// the path and byte range of the written place, and borrowing a field gives a `Tracked` pointer
+/// to it that records into the same tracker. Fields can only be changed through `p!` writes, so
+/// `Tracked` never hands out references.
+pub struct Tracked<'a, T: ?Sized> {
+    ptr: NonNull<T>,
+    root: NonNull<u8>,
+    path: PlacePath,
+    tracker: &'a Tracker,
+    phantom: PhantomData<(&'a mut (), *mut T)>,
+}
+
+impl<'a, T: ?Sized> Tracked<'a, T> {
+    /// The path of this place in the tracked value.
+    pub fn path(&self) -> &PlacePath {
+        &self.path
+    }
+    pub fn tracker(&self) -> &'a Tracker {
+        self.tracker
+    }
+    fn project<P>(&self, p: &P) -> (*mut P::Target, PlacePath)
+    where
+        P: NamedProjection<Source = T> + ByteProjection + ?Sized,
+    {
+        let ptr = self.ptr.as_ptr();
+        let place = unsafe { p.borrow::<*mut _, *mut _>(&raw const ptr) };
+        let mut path = self.path.clone();
+        p.push_path(&mut path);
+        (place, path)
+    }
+    fn with_place<U: ?Sized>(&self, place: *mut U, path: PlacePath) -> Tracked<'a, U> {
+        Tracked {
+            ptr: unsafe { NonNull::new_unchecked(place) },
+            root: self.root,
+            path,
+            tracker: self.tracker,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> HasPlace for Tracked<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'b: 'a, P> PlaceBorrow<'a, P, Tracked<'a, P::Target>> for Tracked<'b, P::Source>
+where
+    P: NamedProjection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> Tracked<'a, P::Target> {
+        let this = unsafe { &*ptr };
+        let (place, path) = this.project(p);
+        this.with_place(place, path)
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for Tracked<'_, P::Source>
+where
+    P: NamedProjection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        let ptr = unsafe { (*ptr).ptr.as_ptr() };
+        unsafe { p.read(&raw const ptr) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for Tracked<'_, P::Source>
+where
+    P: NamedProjection + ByteProjection + ?Sized,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        let this = unsafe { &*ptr };
+        let (place, path) = this.project(p);
+        let start = place.addr() - this.root.addr().get();
+        this.tracker
+            .record(path, start..start + size_of::<P::Target>());
+        // The place is initialized, so drop the old value like an assignment does.
+        unsafe { *place = x }
+    }
+}
+
+unsafe impl<'a, P, I> PlaceIndex<P, I> for Tracked<'a, P::Source>
+where
+    P: NamedProjection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: NamedProjection,
+{
+    type Output = Tracked<'a, <P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let this = unsafe { &*ptr };
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        let (place, mut path) = this.project(p);
+        q.push_path(&mut path);
+        let place = unsafe { q.borrow::<*mut _, *mut _>(&raw const place) };
+        this.with_place(place, path)
+    }
+}
+
+// `Tracked` comes from a `&mut` and never hands out references, so aliasing `Tracked` pointers
+// that copy values in and out are fine.
+unsafe impl<'a, 'b: 'a, P> SafePlaceBorrow<'a, P, Tracked<'a, P::Target>> for Tracked<'b, P::Source> where
+    P: NamedProjection + SafeProjection + ?Sized
+{
+}
+unsafe impl<P> SafePlaceRead<P> for Tracked<'_, P::Source>
+where
+    P: NamedProjection + SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceWrite<P> for Tracked<'_, P::Source>
+where
+    P: NamedProjection + SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for Tracked<'_, P::Source>
+where
+    P: NamedProjection + SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: NamedProjection + SafeProjection,
+{
+}
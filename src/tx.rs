@@ -0,0 +1,197 @@
+use std::{cell::RefCell, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+
+use crate::*;
+
+/// An old value saved by a write: its offset in the value of the transaction, and its bytes.
+struct Undo {
+    offset: usize,
+    bytes: Box<[MaybeUninit<u8>]>,
+}
+
+/// A transaction over a value: writes through its `TxPtr`s save the bytes they overwrite in an
+/// undo log, so that `rollback` can restore the value. Dropping a transaction without calling
+/// `commit` rolls it back.
+///
+/// Only `Copy` places can be written, so that restoring bytes can't duplicate or leak values.
+pub struct Tx<'a, T: ?Sized> {
+    root: NonNull<T>,
+    log: RefCell<Vec<Undo>>,
+    phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: ?Sized> Tx<'a, T> {
+    pub fn new(x: &'a mut T) -> Self {
+        Self {
+            root: NonNull::from(x),
+            log: RefCell::default(),
+            phantom: PhantomData,
+        }
+    }
+    /// Pointer to the whole value. Writes through it and pointers borrowed from it are logged.
+    pub fn ptr(&self) -> TxPtr<'_, T> {
+        TxPtr {
+            ptr: self.root,
+            tx: self.erased(),
+        }
+    }
+    /// Number of writes in the undo log.
+    pub fn log_len(&self) -> usize {
+        self.log.borrow().len()
+    }
+    /// Keep the changes.
+    pub fn commit(self) -> &'a mut T {
+        self.log.borrow_mut().clear();
+        let root = self.root;
+        drop(self);
+        unsafe { &mut *root.as_ptr() }
+    }
+    /// Undo the writes, last first, so that overlapping writes restore the oldest bytes.
+    pub fn rollback(self) -> &'a mut T {
+        let root = self.root;
+        drop(self);
+        unsafe { &mut *root.as_ptr() }
+    }
+
+    fn erased(&self) -> TxRef<'_> {
+        TxRef {
+            root: self.root.cast(),
+            log: &self.log,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Tx<'_, T> {
+    fn drop(&mut self) {
+        let root = self.root.cast::<MaybeUninit<u8>>();
+        for undo in self.log.get_mut().drain(..).rev() {
+            unsafe {
+                let dst = root.add(undo.offset).as_ptr();
+                dst.copy_from_nonoverlapping(undo.bytes.as_ptr(), undo.bytes.len());
+            }
+        }
+    }
+}
+
+/// The parts of a `Tx` that its pointers need, without the type of the whole value.
+#[derive(Clone, Copy)]
+struct TxRef<'tx> {
+    root: NonNull<u8>,
+    log: &'tx RefCell<Vec<Undo>>,
+}
+
+/// A pointer into the value of a `Tx`. Reads are plain, writes through `p!` save the old bytes of
+/// the written place first. Like `Tracked`, it never hands out references.
+pub struct TxPtr<'tx, T: ?Sized> {
+    ptr: NonNull<T>,
+    tx: TxRef<'tx>,
+}
+
+impl<'tx, T: ?Sized> TxPtr<'tx, T> {
+    fn project<P>(&self, p: &P) -> NonNull<P::Target>
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        unsafe { p.borrow(&raw const self.ptr) }
+    }
+}
+
+impl<T: ?Sized> Clone for TxPtr<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for TxPtr<'_, T> {}
+
+impl<T: ?Sized> HasPlace for TxPtr<'_, T> {
+    type Target = T;
+}
+
+unsafe impl<'a, 'tx: 'a, P> PlaceBorrow<'a, P, TxPtr<'a, P::Target>> for TxPtr<'tx, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> TxPtr<'a, P::Target> {
+        let this = unsafe { &*ptr };
+        TxPtr {
+            ptr: this.project(p),
+            tx: this.tx,
+        }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for TxPtr<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).read() }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for TxPtr<'_, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: Copy,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        let this = unsafe { &*ptr };
+        let place = this.project(p);
+        let mut bytes = Box::new_uninit_slice(size_of::<P::Target>());
+        unsafe {
+            let old = place.cast::<MaybeUninit<u8>>().as_ptr();
+            bytes
+                .as_mut_ptr()
+                .copy_from_nonoverlapping(old, bytes.len());
+        }
+        let offset = place.addr().get() - this.tx.root.addr().get();
+        this.tx.log.borrow_mut().push(Undo { offset, bytes });
+        unsafe { place.write(x) }
+    }
+}
+
+unsafe impl<'a, P, I> PlaceIndex<P, I> for TxPtr<'a, P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = TxPtr<'a, <P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        let this = unsafe { &*ptr };
+        let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+        let place = this.project(p);
+        TxPtr {
+            ptr: unsafe { q.borrow(&raw const place) },
+            tx: this.tx,
+        }
+    }
+}
+
+// The value is borrowed mutably by the `Tx` and only accessed by copying values in and out, so
+// aliasing `TxPtr`s are fine.
+unsafe impl<'a, 'tx: 'a, P> SafePlaceBorrow<'a, P, TxPtr<'a, P::Target>> for TxPtr<'tx, P::Source> where
+    P: SafeProjection + ?Sized
+{
+}
+unsafe impl<P> SafePlaceRead<P> for TxPtr<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceWrite<P> for TxPtr<'_, P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P, I> SafePlaceIndex<P, I> for TxPtr<'_, P::Source>
+where
+    P: SafeProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: SafeProjection,
+{
+}
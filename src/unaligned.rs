@@ -0,0 +1,102 @@
+use std::ptr::NonNull;
+
+use crate::*;
+
+/// A pointer to a place that may be misaligned, e.g. a field of a `#[repr(packed)]` struct or a
+/// struct in the middle of a byte buffer. Reads and writes are unaligned.
+///
+/// Borrowing a reference requires an `AlignedProjection`, so fields of packed structs can't be
+/// borrowed as references, and panics if the pointer itself turns out to be misaligned.
+pub struct UnalignedPtr<T: ?Sized>(NonNull<T>);
+
+impl<T: ?Sized> UnalignedPtr<T> {
+    pub const fn new(ptr: NonNull<T>) -> Self {
+        Self(ptr)
+    }
+    pub const fn as_ptr(&self) -> *mut T {
+        self.0.as_ptr()
+    }
+    unsafe fn project<P>(&self, p: &P) -> *mut P::Target
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        let ptr = self.0.as_ptr();
+        unsafe { p.borrow::<*mut _, *mut _>(&raw const ptr) }
+    }
+}
+
+impl<T: ?Sized> Clone for UnalignedPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for UnalignedPtr<T> {}
+
+impl<T: ?Sized> HasPlace for UnalignedPtr<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, UnalignedPtr<P::Target>> for UnalignedPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> UnalignedPtr<P::Target> {
+        unsafe { UnalignedPtr(NonNull::new_unchecked((*ptr).project(p))) }
+    }
+}
+unsafe impl<'a, P> PlaceBorrow<'a, P, &'a P::Target> for UnalignedPtr<P::Source>
+where
+    P: AlignedProjection + ?Sized,
+    P::Target: Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Shared;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> &'a P::Target {
+        unsafe {
+            let place = (*ptr).project(p);
+            assert!(place.is_aligned(), "cannot borrow a misaligned place");
+            &*place
+        }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for UnalignedPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).read_unaligned() }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for UnalignedPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).write_unaligned(x) }
+    }
+}
+
+unsafe impl<P, I> PlaceIndex<P, I> for UnalignedPtr<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+{
+    type Output = UnalignedPtr<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let place = (*ptr).project(p);
+            let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+            UnalignedPtr(NonNull::new_unchecked(
+                q.borrow::<*mut _, *mut _>(&raw const place),
+            ))
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    cell::{Cell, RefCell, UnsafeCell},
+    collections::BTreeMap,
+    mem::MaybeUninit,
+    ops::Range,
+};
+
+use crate::*;
+
+/// A write recorded by a `Versioned`: the version it created and the place it wrote.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Revision {
+    pub version: u64,
+    pub path: PlacePath,
+    pub bytes: Range<usize>,
+}
+
+/// The bytes a write overwrote.
+struct OldBytes {
+    version: u64,
+    path: PlacePath,
+    bytes: Box<[MaybeUninit<u8>]>,
+}
+
+/// A value that keeps the history of its writes. Each write with `p!((*v).a.b = x)` creates a
+/// new version and saves the overwritten bytes, keyed by the byte range of the place, so that
+/// `read_at` can read any place as it was at an earlier version without snapshots of the whole
+/// value.
+///
+/// Like `Tx`, only `Copy` places can be written and read. Version 0 is the initial value.
+pub struct Versioned<T> {
+    value: UnsafeCell<T>,
+    version: Cell<u64>,
+    history: RefCell<BTreeMap<(usize, usize), Vec<OldBytes>>>,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(x: T) -> Self {
+        Self {
+            value: UnsafeCell::new(x),
+            version: Cell::new(0),
+            history: RefCell::default(),
+        }
+    }
+    pub fn version(&self) -> u64 {
+        self.version.get()
+    }
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+    /// All the writes, oldest first.
+    pub fn revisions(&self) -> Vec<Revision> {
+        let mut revisions: Vec<_> = self
+            .history
+            .borrow()
+            .iter()
+            .flat_map(|(&(start, end), olds)| {
+                olds.iter().map(move |old| Revision {
+                    version: old.version,
+                    path: old.path.clone(),
+                    bytes: start..end,
+                })
+            })
+            .collect();
+        revisions.sort_by_key(|rev| rev.version);
+        revisions
+    }
+
+    /// The target of `p` as it was at `version`: its current bytes, with the bytes overwritten
+    /// by later writes put back, newest first. Panics if `version` is in the future.
+    pub fn read_at<P>(&self, p: &P, version: u64) -> P::Target
+    where
+        P: SafeProjection<Source = T> + ?Sized,
+        P::Target: Copy,
+    {
+        assert!(
+            version <= self.version(),
+            "version {version} doesn't exist yet"
+        );
+        let start = p.offset(());
+        let len = size_of::<P::Target>();
+        let mut out = MaybeUninit::<P::Target>::uninit();
+        let buf = out.as_mut_ptr().cast::<MaybeUninit<u8>>();
+        unsafe {
+            let current = self.value.get().cast::<MaybeUninit<u8>>().add(start);
+            buf.copy_from_nonoverlapping(current, len);
+        }
+        let history = self.history.borrow();
+        let mut undo: Vec<_> = history
+            .range(..(start + len, 0))
+            .filter(|&(&(_, end), _)| end > start)
+            .flat_map(|(&(old_start, _), olds)| olds.iter().map(move |old| (old_start, old)))
+            .filter(|(_, old)| old.version > version)
+            .collect();
+        undo.sort_by_key(|(_, old)| std::cmp::Reverse(old.version));
+        for (old_start, old) in undo {
+            // Copy the part of the old bytes that overlaps the place.
+            let from = start.max(old_start);
+            let to = (start + len).min(old_start + old.bytes.len());
+            unsafe {
+                buf.add(from - start)
+                    .copy_from_nonoverlapping(old.bytes.as_ptr().add(from - old_start), to - from);
+            }
+        }
+        unsafe { out.assume_init() }
+    }
+}
+
+impl<T: Copy> Versioned<T> {
+    /// The whole value at `version`.
+    pub fn value_at(&self, version: u64) -> T {
+        self.read_at(&NoopProj::default(), version)
+    }
+}
+
+impl<T> HasPlace for Versioned<T> {
+    type Target = T;
+}
+
+unsafe impl<P> PlaceRead<P> for Versioned<P::Source>
+where
+    P: Projection + ByteProjection + ?Sized,
+    P::Source: Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        let whole = unsafe { (*ptr).value.get() };
+        unsafe { p.read(&raw const whole) }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for Versioned<P::Source>
+where
+    P: NamedProjection + ByteProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: Copy,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target) {
+        let path = PlacePath::of(p);
+        let this = unsafe { &*ptr };
+        let whole = this.value.get();
+        let place = unsafe { p.borrow::<*mut _, *mut _>(&raw const whole) };
+        let start = place.addr() - whole.addr();
+        let len = size_of::<P::Target>();
+        let mut bytes = Box::new_uninit_slice(len);
+        unsafe {
+            let old = place.cast::<MaybeUninit<u8>>();
+            bytes.as_mut_ptr().copy_from_nonoverlapping(old, len);
+        }
+        let version = this.version.get() + 1;
+        this.version.set(version);
+        this.history
+            .borrow_mut()
+            .entry((start, start + len))
+            .or_default()
+            .push(OldBytes {
+                version,
+                path,
+                bytes,
+            });
+        unsafe { place.write(x) }
+    }
+}
+
+// The value is only accessed by copying values in and out, and never borrowed.
+unsafe impl<P> SafePlaceRead<P> for Versioned<P::Source>
+where
+    P: SafeProjection + AlignedProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: Copy,
+{
+}
+unsafe impl<P> SafePlaceWrite<P> for Versioned<P::Source>
+where
+    P: NamedProjection + SafeProjection + AlignedProjection + ?Sized,
+    P::Source: Sized,
+    P::Target: Copy,
+{
+}
@@ -0,0 +1,116 @@
+use std::ptr::NonNull;
+
+use crate::*;
+
+/// A field that may only be read, e.g. a status register. `#[derive(FieldProjections)]` gives it a
+/// projection to `T` that only implements `ReadableProjection`.
+#[repr(transparent)]
+pub struct ReadOnly<T>(T);
+impl<T: Copy> ReadOnly<T> {
+    pub const fn new(x: T) -> Self {
+        Self(x)
+    }
+    pub const fn get(&self) -> T {
+        self.0
+    }
+}
+
+/// A field that may only be written, e.g. a transmit register. `#[derive(FieldProjections)]` gives
+/// it a projection to `T` that only implements `WritableProjection`.
+#[repr(transparent)]
+pub struct WriteOnly<T>(T);
+impl<T: Copy> WriteOnly<T> {
+    pub const fn new(x: T) -> Self {
+        Self(x)
+    }
+}
+
+/// A pointer to memory-mapped device registers: every read and write through it is volatile, and
+/// only touches the projected field.
+///
+/// Borrowing a subplace requires a projection that allows both reads and writes, so that a
+/// `ReadOnly` or `WriteOnly` field can't escape its restriction through a borrow.
+pub struct VolatilePtr<T: ?Sized>(NonNull<T>);
+
+impl<T: ?Sized> VolatilePtr<T> {
+    /// # Safety
+    ///
+    /// `ptr` must be valid for volatile reads and writes for as long as the pointer and
+    /// the ones derived from it are used.
+    pub const unsafe fn new(ptr: NonNull<T>) -> Self {
+        Self(ptr)
+    }
+    pub const fn as_ptr(&self) -> *mut T {
+        self.0.as_ptr()
+    }
+    unsafe fn project<P>(&self, p: &P) -> *mut P::Target
+    where
+        P: Projection<Source = T> + ByteProjection + ?Sized,
+    {
+        let ptr = self.0.as_ptr();
+        unsafe { p.borrow::<*mut _, *mut _>(&raw const ptr) }
+    }
+}
+
+impl<T: ?Sized> Clone for VolatilePtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for VolatilePtr<T> {}
+
+impl<T: ?Sized> HasPlace for VolatilePtr<T> {
+    type Target = T;
+}
+
+unsafe impl<'a, P> PlaceBorrow<'a, P, VolatilePtr<P::Target>> for VolatilePtr<P::Source>
+where
+    P: ReadableProjection + WritableProjection + ByteProjection + ?Sized,
+{
+    const BORROW_KIND: BorrowKind = BorrowKind::Untracked;
+    unsafe fn borrow(ptr: *const Self, p: &P) -> VolatilePtr<P::Target> {
+        unsafe { VolatilePtr(NonNull::new_unchecked((*ptr).project(p))) }
+    }
+}
+
+unsafe impl<P> PlaceRead<P> for VolatilePtr<P::Source>
+where
+    P: ReadableProjection + ByteProjection + ?Sized,
+{
+    unsafe fn read(ptr: *const Self, p: &P) -> P::Target
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).read_volatile() }
+    }
+}
+
+unsafe impl<P> PlaceWrite<P> for VolatilePtr<P::Source>
+where
+    P: WritableProjection + ByteProjection + ?Sized,
+{
+    unsafe fn write(ptr: *mut Self, p: &P, x: P::Target)
+    where
+        P::Target: Sized,
+    {
+        unsafe { (*ptr).project(p).write_volatile(x) }
+    }
+}
+
+unsafe impl<P, I> PlaceIndex<P, I> for VolatilePtr<P::Source>
+where
+    P: ReadableProjection + WritableProjection + ByteProjection + ?Sized,
+    P::Target: IndexProjection<I>,
+    <P::Target as IndexProjection<I>>::Proj: ReadableProjection + WritableProjection,
+{
+    type Output = VolatilePtr<<P::Target as IndexProjection<I>>::Output>;
+    unsafe fn index(ptr: *const Self, p: &P, idx: I) -> Self::Output {
+        unsafe {
+            let place = (*ptr).project(p);
+            let q = <P::Target as IndexProjection<I>>::index_proj(idx);
+            VolatilePtr(NonNull::new_unchecked(
+                q.borrow::<*mut _, *mut _>(&raw const place),
+            ))
+        }
+    }
+}